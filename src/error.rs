@@ -0,0 +1,74 @@
+use std::fmt;
+
+use ndarray::ShapeError;
+use ndarray_rand::rand_distr::NormalError;
+use ndarray_stats::errors::{EmptyInput, MinMaxError, MultiInputError};
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ClusteringError {
+    InvalidParameters(String),
+    ShapeMismatch(String),
+    // Every agent collapsed to the same or an unusable state
+    DegeneratePopulation(String),
+    NanEncountered(String),
+    // Singular matrices and failed decompositions
+    Numerical(String),
+    Plotting(String)
+}
+
+pub type Result<T> = std::result::Result<T, ClusteringError>;
+
+impl fmt::Display for ClusteringError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ClusteringError::InvalidParameters(message) => write!(f, "invalid parameters: {}", message),
+            ClusteringError::ShapeMismatch(message) => write!(f, "shape mismatch: {}", message),
+            ClusteringError::DegeneratePopulation(message) => write!(f, "degenerate population: {}", message),
+            ClusteringError::NanEncountered(message) => write!(f, "NaN encountered: {}", message),
+            ClusteringError::Numerical(message) => write!(f, "numerical failure: {}", message),
+            ClusteringError::Plotting(message) => write!(f, "plotting failure: {}", message)
+        }
+    }
+}
+
+impl std::error::Error for ClusteringError {}
+
+impl From<ShapeError> for ClusteringError {
+    fn from(error: ShapeError) -> Self {
+        ClusteringError::ShapeMismatch(error.to_string())
+    }
+}
+
+impl From<MultiInputError> for ClusteringError {
+    fn from(error: MultiInputError) -> Self {
+        match error {
+            MultiInputError::EmptyInput => ClusteringError::InvalidParameters("empty input".into()),
+            MultiInputError::ShapeMismatch(mismatch) => ClusteringError::ShapeMismatch(format!(
+                "{:?} and {:?}",
+                mismatch.first_shape,
+                mismatch.second_shape
+            ))
+        }
+    }
+}
+
+impl From<MinMaxError> for ClusteringError {
+    fn from(error: MinMaxError) -> Self {
+        match error {
+            MinMaxError::EmptyInput => ClusteringError::InvalidParameters("empty input".into()),
+            MinMaxError::UndefinedOrder => ClusteringError::NanEncountered("values cannot be ordered".into())
+        }
+    }
+}
+
+impl From<EmptyInput> for ClusteringError {
+    fn from(_: EmptyInput) -> Self {
+        ClusteringError::InvalidParameters("empty input".into())
+    }
+}
+
+impl From<NormalError> for ClusteringError {
+    fn from(error: NormalError) -> Self {
+        ClusteringError::InvalidParameters(error.to_string())
+    }
+}
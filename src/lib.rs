@@ -1,13 +1,18 @@
-use ndarray::{self, ArrayBase, Dim, OwnedRepr};
+use ndarray::{self, Array1, Array2};
 use linfa::DatasetBase;
 use plotly::color::NamedColor;
 
-pub type Data = DatasetBase<ArrayBase<OwnedRepr<f64>, Dim<[usize; 2]>>, ArrayBase<OwnedRepr<usize>, Dim<[usize; 1]>>>;
+pub type Data<F = f64> = DatasetBase<Array2<F>, Array1<usize>>;
 
 pub mod model;
 pub mod plot;
 
 pub mod utility;
+pub mod tuning;
+pub mod preprocessing;
+pub mod error;
+
+pub use error::ClusteringError;
 
 pub const IRIS_LABELS: [&'static str; 3] = [
     "Setosa",
@@ -1,3 +1,4 @@
+use linfa::ParamGuard;
 use linfa_datasets::iris;
 use plotly::ImageFormat;
 use std::error::Error;
@@ -8,23 +9,31 @@ use clusterization::{
         solution::{self, Discrete},
         metric,
         kmeans,
+        initialization,
+        memetic,
+        mutation,
         gravity,
         whales
     },
-    plot::{cluster_map, prediction_map, scatter_matrix},
+    plot::{cluster_map, palette::Classes, prediction_map, scatter_matrix},
+    ClusteringError,
     Data,
 };
 
 
-fn evaluate_and_save_results(data: &Data, prediction: &Discrete, truth: &Discrete, algorithm_name: &'static str) {
-    let accuracy = 100.0 * metric::accuracy(truth, prediction).unwrap();
+fn evaluate_and_save_results(
+    data: &Data,
+    prediction: &Discrete,
+    truth: &Discrete,
+    algorithm_name: &'static str
+) -> Result<(), ClusteringError> {
+    let accuracy = 100.0 * metric::accuracy(truth, prediction)?;
 
     let title = format!("Iris - {}, {:.2}% accuracy", algorithm_name, accuracy);
 
     let dest = format!("images/accuracy_{}.png", algorithm_name);
 
-    prediction_map::plot(data.clone(), prediction.clone(), 0, 1, &title)
-        .unwrap()
+    prediction_map::plot(data.clone(), prediction.clone(), 0, 1, &title)?
         .write_image(
             &dest,
             ImageFormat::PNG,
@@ -35,8 +44,7 @@ fn evaluate_and_save_results(data: &Data, prediction: &Discrete, truth: &Discret
 
     let dest = format!("images/clusters_{}.png", algorithm_name);
 
-    cluster_map::plot(data.clone(), prediction.clone(), 0, 1, &title)
-        .unwrap()
+    cluster_map::plot_with(data.clone(), prediction.clone(), 0, 1, &title, &Classes::iris())?
         .write_image(
             &dest,
             ImageFormat::PNG,
@@ -44,6 +52,8 @@ fn evaluate_and_save_results(data: &Data, prediction: &Discrete, truth: &Discret
             420,
             1.0
         );
+
+    Ok(())
 }
 
 
@@ -53,52 +63,48 @@ fn main() -> Result<(), Box<dyn Error>> {
 
     // GSA
 
-    let params = gravity::Parameters {
-        n_classes: 3,
-        n_agents: 10,
-        max_iterations: 500,
-        initial_gravity: 1.0,
-        gravity_decay: 0.01,
-        distance: gravity::Distance::Cosine,
-        normalization: gravity::Normalization::MinMax
-    };
+    let params = gravity::Parameters::builder(3)
+        .n_agents(10)
+        .max_iterations(500)
+        .distance(gravity::Distance::Cosine)
+        .normalization(gravity::Normalization::MinMax)
+        .check()?;
 
     let solution = gravity::fit(&data, params)?;
 
     let prediction = solution
-        .to_discrete()
-        .matched_with(&truth)
-        .unwrap();
+        .to_discrete()?
+        .matched_with(&truth)?;
 
-    evaluate_and_save_results(&data, &prediction, &truth, "gravity");
+    evaluate_and_save_results(&data, &prediction, &truth, "gravity")?;
 
 
     // WOA
 
-    // let params = whales::Parameters {
-    //     n_classes: 3,
-    //     n_agents: 50,
-    //     max_iterations: 2000,
-    //     spiral_constant: 1.0,
-    //     n_spiral_samples: 50
-    // };
+    // let params = whales::Parameters::builder(3)
+    //     .n_agents(50)
+    //     .max_iterations(2000)
+    //     .spiral_constant(1.0)
+    //     .n_spiral_samples(50)
+    //     .initialization(initialization::Initialization::Random)
+    //     .refinement(memetic::Refinement::None)
+    //     .mutation(mutation::Mutation::None)
+    //     .check()?;
 
     // let solution = whales::fit(&data, params)?;
     // let prediction = solution
-    //     .to_discrete()
-    //     .matched_with(&truth)
-    //     .unwrap();
+    //     .to_discrete()?
+    //     .matched_with(&truth)?;
 
-    // evaluate_and_save_results(&data, &prediction, &truth, "whales");
+    // evaluate_and_save_results(&data, &prediction, &truth, "whales")?;
 
 
     // K-means
 
     let prediction = kmeans::fit(&data, 3, 200, 1e-4)?
-        .matched_with(&truth)
-        .unwrap();
+        .matched_with(&truth)?;
 
-    evaluate_and_save_results(&data, &prediction, &truth, "kmeans");
+    evaluate_and_save_results(&data, &prediction, &truth, "kmeans")?;
 
     // Evaluation
 
@@ -0,0 +1,100 @@
+use linfa::Float;
+use ndarray::{Array1, Array2, Axis};
+
+use crate::{error::ClusteringError, preprocessing::missing, Data};
+
+use super::solution::Discrete;
+
+#[derive(Debug, Clone, Copy)]
+pub enum Linkage {
+    Single,
+    Complete,
+    Average,
+    Ward
+}
+
+// Lance-Williams update of the distance between cluster k and the merge of i and j
+fn lance_williams<F: Float>(linkage: Linkage, d_ik: F, d_jk: F, d_ij: F, n_i: F, n_j: F, n_k: F) -> F {
+    match linkage {
+        Linkage::Single => d_ik.min(d_jk),
+        Linkage::Complete => d_ik.max(d_jk),
+        Linkage::Average => (n_i * d_ik + n_j * d_jk) / (n_i + n_j),
+        Linkage::Ward => ((n_i + n_k) * d_ik + (n_j + n_k) * d_jk - n_k * d_ij) / (n_i + n_j + n_k)
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, n_clusters: usize, linkage: Linkage) -> Result<Discrete, ClusteringError> {
+    missing::check(data)?;
+
+    let records = &data.records;
+    let n_samples = records.nrows();
+
+    if n_clusters == 0 || n_clusters > n_samples {
+        return Err(ClusteringError::InvalidParameters(format!("cannot form {} clusters from {} samples", n_clusters, n_samples)));
+    }
+
+    // Ward's criterion is defined on squared Euclidean distances
+    let mut distances = Array2::<F>::zeros((n_samples, n_samples));
+
+    for (i, a) in records.axis_iter(Axis(0)).enumerate() {
+        for (j, b) in records.axis_iter(Axis(0)).enumerate().skip(i + 1) {
+            let squared = (&a - &b).mapv_into(|x| x * x).sum();
+            let distance = match linkage {
+                Linkage::Ward => squared,
+                _ => squared.sqrt()
+            };
+            distances[[i, j]] = distance;
+            distances[[j, i]] = distance;
+        }
+    }
+
+    let mut active = vec![true; n_samples];
+    let mut sizes = vec![F::one(); n_samples];
+    let mut labels = (0..n_samples).collect::<Vec<_>>();
+
+    for _ in 0..n_samples - n_clusters {
+        let mut closest = (0, 0, F::infinity());
+
+        for i in (0..n_samples).filter(|&i| active[i]) {
+            for j in (i + 1..n_samples).filter(|&j| active[j]) {
+                if distances[[i, j]] < closest.2 {
+                    closest = (i, j, distances[[i, j]]);
+                }
+            }
+        }
+
+        let (i, j, d_ij) = closest;
+
+        for k in (0..n_samples).filter(|&k| active[k] && k != i && k != j) {
+            let distance = lance_williams(
+                linkage,
+                distances[[i, k]],
+                distances[[j, k]],
+                d_ij,
+                sizes[i],
+                sizes[j],
+                sizes[k]
+            );
+            distances[[i, k]] = distance;
+            distances[[k, i]] = distance;
+        }
+
+        sizes[i] = sizes[i] + sizes[j];
+        active[j] = false;
+        labels.iter_mut().filter(|label| **label == j).for_each(|label| *label = i);
+    }
+
+    // Surviving cluster representatives are renumbered 0..n_clusters in order
+    let mut renumbered = vec![0; n_samples];
+
+    for (cluster, i) in (0..n_samples).filter(|&i| active[i]).enumerate() {
+        renumbered[i] = cluster;
+    }
+
+    let indicators = labels
+        .iter()
+        .map(|&label| renumbered[label])
+        .collect::<Array1<usize>>();
+
+    Ok(Discrete::from_prediction(indicators, n_clusters))
+}
@@ -0,0 +1,126 @@
+use linfa::Float;
+use rand::Rng;
+
+use super::{history::History, partition::Partition, solution::Discrete};
+use crate::{error::ClusteringError, preprocessing::missing, utility::sampling::{seeded_rng, ExtendedRng}, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Cooling {
+    Exponential { rate: f64 },
+    Linear,
+    Logarithmic
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub max_iterations: usize,
+    pub initial_temperature: f64,
+    pub cooling: Cooling,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        if !self.initial_temperature.is_finite() || self.initial_temperature <= 0.0 {
+            return Err(ClusteringError::InvalidParameters("initial temperature must be finite and positive".into()));
+        }
+
+        match self.cooling {
+            Cooling::Exponential { rate } if !(rate > 0.0 && rate <= 1.0) => Err(ClusteringError::InvalidParameters(format!(
+                "exponential cooling rate must lie in (0, 1], got {}",
+                rate
+            ))),
+            _ => Ok(())
+        }
+    }
+}
+
+fn temperature(initial: f64, cooling: Cooling, time: usize, max_time: usize) -> f64 {
+    match cooling {
+        Cooling::Exponential { rate } => initial * rate.powi(time as i32),
+        Cooling::Linear => initial * (1.0 - time as f64 / max_time as f64),
+        Cooling::Logarithmic => initial / (time as f64 + std::f64::consts::E).ln()
+    }
+}
+
+// Single-sample relabeling moves accepted by the Metropolis criterion on the
+// within-cluster sum of squares
+pub fn anneal<F: Float>(data: &Data<F>, initial: &Discrete, params: Parameters) -> Result<Discrete, ClusteringError> {
+    anneal_with(data, initial, params, &mut seeded_rng(params.seed), None)
+}
+
+fn anneal_with<F: Float, R: Rng>(
+    data: &Data<F>,
+    initial: &Discrete,
+    params: Parameters,
+    rng: &mut R,
+    mut history: Option<&mut History>
+) -> Result<Discrete, ClusteringError> {
+    params.validate()?;
+
+    let Parameters {
+        max_iterations,
+        initial_temperature,
+        cooling,
+        ..
+    } = params;
+
+    let mut partition = Partition::new(data, initial)?;
+
+    let mut best = partition.to_discrete();
+    let mut best_sse = partition.sse();
+
+    if partition.n_classes() < 2 {
+        return Ok(best);
+    }
+
+    for time in 0..max_iterations {
+        let temperature = F::cast(temperature(initial_temperature, cooling, time, max_iterations));
+
+        let sample = rng.gen_range(0..partition.n_samples());
+        let target = rng.gen_range_excluding(0..partition.n_classes(), partition.label(sample));
+
+        let delta = partition.delta(sample, target);
+
+        let accept = delta < F::zero()
+            || (temperature > F::zero() && F::cast(rng.gen::<f64>()) < (-delta / temperature).exp());
+
+        if accept {
+            partition.apply(sample, target);
+
+            if partition.sse() < best_sse {
+                best_sse = partition.sse();
+                best = partition.to_discrete();
+            }
+        }
+
+        if let Some(history) = history.as_deref_mut() {
+            history.record(&[-partition.sse()]);
+        }
+    }
+
+    Ok(best)
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Discrete, ClusteringError> {
+    run(data, params, None)
+}
+
+// Also returns the negated within-cluster sum of squares of the current
+// labeling after every iteration, so that higher is better as in the other histories
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Discrete, History), ClusteringError> {
+    let mut history = History::new();
+    let best = run(data, params, Some(&mut history))?;
+
+    Ok((best, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, history: Option<&mut History>) -> Result<Discrete, ClusteringError> {
+    missing::check(data)?;
+
+    let mut rng = seeded_rng(params.seed);
+    let initial = Partition::random(data, params.n_classes, &mut rng)?.to_discrete();
+
+    anneal_with(data, &initial, params, &mut rng, history)
+}
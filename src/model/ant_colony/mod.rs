@@ -0,0 +1,167 @@
+use std::cmp::Ordering;
+
+use linfa::Float;
+use ndarray::{Array1, Array2};
+use rand::{distributions::{Distribution, WeightedIndex}, Rng};
+
+use super::{history::History, partition::Partition, solution::Discrete, tabu};
+use crate::{error::ClusteringError, preprocessing::missing, utility::sampling::seeded_rng, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub enum LocalSearch {
+    None,
+    Tabu { max_iterations: usize, tenure: usize }
+}
+
+// Shelokar et al. (2004): pheromone trails of shape n_samples x n_classes
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub evaporation: f64,
+    pub exploitation: f64,
+    pub n_elite: usize,
+    pub local_search: LocalSearch,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        if self.n_classes == 0 {
+            return Err(ClusteringError::InvalidParameters("number of classes must be positive".into()));
+        }
+
+        if self.n_agents == 0 {
+            return Err(ClusteringError::InvalidParameters("colony needs at least one ant".into()));
+        }
+
+        if self.max_iterations == 0 {
+            return Err(ClusteringError::InvalidParameters("number of iterations must be positive".into()));
+        }
+
+        if !(0.0..=1.0).contains(&self.evaporation) {
+            return Err(ClusteringError::InvalidParameters(format!("evaporation must lie in [0, 1], got {}", self.evaporation)));
+        }
+
+        if !(0.0..=1.0).contains(&self.exploitation) {
+            return Err(ClusteringError::InvalidParameters(format!("exploitation probability must lie in [0, 1], got {}", self.exploitation)));
+        }
+
+        if self.n_elite > self.n_agents {
+            return Err(ClusteringError::InvalidParameters(format!(
+                "number of elite ants ({}) exceeds the colony size ({})",
+                self.n_elite,
+                self.n_agents
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Discrete, ClusteringError> {
+    run(data, params, None)
+}
+
+// Also returns the fitness of the colony after every iteration
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Discrete, History), ClusteringError> {
+    let mut history = History::new();
+    let best = run(data, params, Some(&mut history))?;
+
+    Ok((best, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, mut history: Option<&mut History>) -> Result<Discrete, ClusteringError> {
+    params.validate()?;
+    missing::check(data)?;
+
+    let n_samples = data.records.nrows();
+
+    let Parameters {
+        n_classes,
+        n_agents,
+        max_iterations,
+        evaporation,
+        exploitation,
+        n_elite,
+        local_search,
+        seed
+    } = params;
+
+    let mut rng = seeded_rng(seed);
+
+    let mut pheromone = Array2::<f64>::from_elem((n_samples, n_classes), 0.01);
+
+    let mut best = Partition::random(data, n_classes, &mut rng)?.to_discrete();
+    let mut best_sse = Partition::new(data, &best)?.sse();
+
+    for _ in 0..max_iterations {
+        let mut colony = (0..n_agents)
+            .map(|_| {
+                // Each ant labels the samples one by one, either greedily or by
+                // roulette over the normalized trail of the sample
+                let labels = pheromone
+                    .rows()
+                    .into_iter()
+                    .map(|trail| match rng.gen_bool(exploitation) {
+                        true => trail
+                            .iter()
+                            .enumerate()
+                            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+                            .map_or(0, |(c, _)| c),
+                        false => WeightedIndex::new(trail.iter())
+                            .map_or(0, |roulette| roulette.sample(&mut rng))
+                    })
+                    .collect::<Array1<usize>>();
+
+                let mut labeling = Discrete::from_prediction(labels, n_classes);
+
+                // Seeded from the colony so that a seeded run stays reproducible
+                if let LocalSearch::Tabu { max_iterations, tenure } = local_search {
+                    labeling = tabu::search(data, &labeling, tabu::Parameters {
+                        n_classes,
+                        max_iterations,
+                        tenure,
+                        n_candidates: None,
+                        seed: Some(rng.gen())
+                    })?;
+                }
+
+                let sse = Partition::new(data, &labeling)?.sse();
+                Ok((sse, labeling))
+            })
+            .collect::<Result<Vec<_>, ClusteringError>>()?;
+
+        colony.sort_by(|(a, _), (b, _)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        if let Some((sse, labeling)) = colony.first() {
+            if *sse < best_sse {
+                best_sse = *sse;
+                best = labeling.clone();
+            }
+        }
+
+        pheromone *= 1.0 - evaporation;
+
+        // Elitist deposit: the best ants of the iteration and the best-so-far labeling
+        let deposits = colony
+            .iter()
+            .take(n_elite)
+            .map(|(sse, labeling)| (*sse, labeling))
+            .chain(std::iter::once((best_sse, &best)));
+
+        for (sse, labeling) in deposits {
+            let amount = 1.0 / sse.to_f64().unwrap_or(f64::INFINITY).max(f64::EPSILON);
+            for (i, &label) in labeling.indicators.iter().enumerate() {
+                pheromone[[i, label]] += amount;
+            }
+        }
+
+        if let Some(history) = history.as_deref_mut() {
+            history.record(&colony.iter().map(|(_, labeling)| labeling.fitness(data)).collect::<Vec<_>>());
+        }
+    }
+
+    Ok(best)
+}
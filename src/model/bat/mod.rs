@@ -0,0 +1,236 @@
+use linfa::{Float, ParamGuard};
+use ndarray::Array2;
+use rand::Rng;
+
+use super::{distance::Measure, history::History, initialization::Initialization, memetic::Refinement, mutation::levy_flight, runner::{self, Settings}, solution::Fuzzy};
+use crate::{error::ClusteringError, utility::sampling::Levy, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub min_frequency: f64,
+    pub max_frequency: f64,
+    pub loudness: f64,
+    pub loudness_decay: f64,
+    pub pulse_rate: f64,
+    pub pulse_rate_growth: f64,
+    pub levy_exponent: f64,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn builder(n_classes: usize) -> ParametersBuilder {
+        ParametersBuilder(Parameters {
+            n_classes,
+            n_agents: 20,
+            max_iterations: 500,
+            min_frequency: 0.0,
+            max_frequency: 2.0,
+            loudness: 1.0,
+            loudness_decay: 0.9,
+            pulse_rate: 0.5,
+            pulse_rate_growth: 0.9,
+            levy_exponent: 1.5,
+            metric: Measure::Euclidean,
+            initialization: Initialization::Random,
+            refinement: Refinement::None,
+            seed: None
+        })
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            n_classes: self.n_classes,
+            n_agents: self.n_agents,
+            max_iterations: self.max_iterations,
+            metric: self.metric,
+            initialization: self.initialization,
+            refinement: self.refinement,
+            seed: self.seed
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        if !(self.min_frequency.is_finite() && self.max_frequency.is_finite() && self.min_frequency <= self.max_frequency) {
+            return Err(ClusteringError::InvalidParameters(format!(
+                "frequency range must be finite and ordered, got [{}, {}]",
+                self.min_frequency,
+                self.max_frequency
+            )));
+        }
+
+        if !(self.loudness.is_finite() && self.loudness >= 0.0) {
+            return Err(ClusteringError::InvalidParameters(format!("loudness must be finite and non-negative, got {}", self.loudness)));
+        }
+
+        if !(self.loudness_decay > 0.0 && self.loudness_decay <= 1.0) {
+            return Err(ClusteringError::InvalidParameters(format!("loudness decay must lie in (0, 1], got {}", self.loudness_decay)));
+        }
+
+        if !(0.0..=1.0).contains(&self.pulse_rate) {
+            return Err(ClusteringError::InvalidParameters(format!("pulse rate must lie in [0, 1], got {}", self.pulse_rate)));
+        }
+
+        if !(self.pulse_rate_growth.is_finite() && self.pulse_rate_growth >= 0.0) {
+            return Err(ClusteringError::InvalidParameters(format!("pulse rate growth must be finite and non-negative, got {}", self.pulse_rate_growth)));
+        }
+
+        Levy::new(self.levy_exponent)?;
+
+        self.settings().validate(1)
+    }
+}
+
+// Unchecked parameters with defaults, turned into Parameters by ParamGuard::check
+#[derive(Debug, Clone, Copy)]
+pub struct ParametersBuilder(Parameters);
+
+impl ParametersBuilder {
+    pub fn n_agents(mut self, n_agents: usize) -> Self {
+        self.0.n_agents = n_agents;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.0.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn min_frequency(mut self, min_frequency: f64) -> Self {
+        self.0.min_frequency = min_frequency;
+        self
+    }
+
+    pub fn max_frequency(mut self, max_frequency: f64) -> Self {
+        self.0.max_frequency = max_frequency;
+        self
+    }
+
+    pub fn loudness(mut self, loudness: f64) -> Self {
+        self.0.loudness = loudness;
+        self
+    }
+
+    pub fn loudness_decay(mut self, loudness_decay: f64) -> Self {
+        self.0.loudness_decay = loudness_decay;
+        self
+    }
+
+    pub fn pulse_rate(mut self, pulse_rate: f64) -> Self {
+        self.0.pulse_rate = pulse_rate;
+        self
+    }
+
+    pub fn pulse_rate_growth(mut self, pulse_rate_growth: f64) -> Self {
+        self.0.pulse_rate_growth = pulse_rate_growth;
+        self
+    }
+
+    pub fn levy_exponent(mut self, levy_exponent: f64) -> Self {
+        self.0.levy_exponent = levy_exponent;
+        self
+    }
+
+    pub fn metric(mut self, metric: Measure) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    pub fn initialization(mut self, initialization: Initialization) -> Self {
+        self.0.initialization = initialization;
+        self
+    }
+
+    pub fn refinement(mut self, refinement: Refinement) -> Self {
+        self.0.refinement = refinement;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.0.seed = Some(seed);
+        self
+    }
+}
+
+impl ParamGuard for ParametersBuilder {
+    type Checked = Parameters;
+    type Error = ClusteringError;
+
+    fn check_ref(&self) -> Result<&Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(&self.0)
+    }
+
+    fn check(self) -> Result<Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    runner::with_history(|history| run(data, params, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    params.validate()?;
+
+    let n_samples = data.records.nrows();
+
+    let Parameters {
+        n_classes,
+        n_agents,
+        min_frequency,
+        max_frequency,
+        loudness,
+        loudness_decay,
+        pulse_rate,
+        pulse_rate_growth,
+        levy_exponent,
+        metric,
+        ..
+    } = params;
+
+    let levy = Levy::new(levy_exponent)?;
+
+    let mut velocities = vec![Array2::<F>::zeros((n_samples, n_classes)); n_agents];
+    let mut loudness = vec![loudness; n_agents];
+    let mut pulse_rates = vec![0.0; n_agents];
+
+    runner::run(data, params.settings(), history, |bats, time, rng| {
+        let best = bats.best.distribution.clone();
+        let mean_loudness = loudness.iter().sum::<f64>() / n_agents as f64;
+
+        for i in 0..n_agents {
+            let frequency = min_frequency + (max_frequency - min_frequency) * rng.gen::<f64>();
+
+            velocities[i] += &((&bats.agents[i].distribution - &best) * F::cast(frequency));
+            let mut candidate = bats.agents[i].clone();
+            candidate.distribution += &velocities[i];
+
+            // Local search around the best bat, with Lévy rather than uniform steps
+            if rng.gen::<f64>() > pulse_rates[i] {
+                candidate.distribution = levy_flight(&bats.agents[i].distribution, &best, &levy, mean_loudness, rng);
+            }
+
+            let candidate_fitness = candidate.fitness_with(data, &metric);
+
+            if candidate_fitness > bats.fitness[i] && rng.gen::<f64>() < loudness[i] {
+                bats.agents[i] = candidate;
+                bats.fitness[i] = candidate_fitness;
+                loudness[i] *= loudness_decay;
+                pulse_rates[i] = pulse_rate * (1.0 - (-pulse_rate_growth * time as f64).exp());
+            }
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,184 @@
+use linfa::{Float, ParamGuard};
+use rand::{distributions::{Distribution, WeightedIndex}, Rng};
+
+use super::{distance::Measure, history::History, initialization::Initialization, memetic::Refinement, runner::{self, Population, Settings}, solution::Fuzzy};
+use crate::{error::ClusteringError, utility::sampling::ExtendedRng, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub abandonment_limit: usize,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn builder(n_classes: usize) -> ParametersBuilder {
+        ParametersBuilder(Parameters {
+            n_classes,
+            n_agents: 20,
+            max_iterations: 500,
+            abandonment_limit: 20,
+            metric: Measure::Euclidean,
+            initialization: Initialization::Random,
+            refinement: Refinement::None,
+            seed: None
+        })
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            n_classes: self.n_classes,
+            n_agents: self.n_agents,
+            max_iterations: self.max_iterations,
+            metric: self.metric,
+            initialization: self.initialization,
+            refinement: self.refinement,
+            seed: self.seed
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        self.settings().validate(1)
+    }
+}
+
+// Unchecked parameters with defaults, turned into Parameters by ParamGuard::check
+#[derive(Debug, Clone, Copy)]
+pub struct ParametersBuilder(Parameters);
+
+impl ParametersBuilder {
+    pub fn n_agents(mut self, n_agents: usize) -> Self {
+        self.0.n_agents = n_agents;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.0.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn abandonment_limit(mut self, abandonment_limit: usize) -> Self {
+        self.0.abandonment_limit = abandonment_limit;
+        self
+    }
+
+    pub fn metric(mut self, metric: Measure) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    pub fn initialization(mut self, initialization: Initialization) -> Self {
+        self.0.initialization = initialization;
+        self
+    }
+
+    pub fn refinement(mut self, refinement: Refinement) -> Self {
+        self.0.refinement = refinement;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.0.seed = Some(seed);
+        self
+    }
+}
+
+impl ParamGuard for ParametersBuilder {
+    type Checked = Parameters;
+    type Error = ClusteringError;
+
+    fn check_ref(&self) -> Result<&Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(&self.0)
+    }
+
+    fn check(self) -> Result<Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+// Moves the membership row of one random sample relative to a random partner
+// source and keeps the candidate only if it is fitter
+fn explore<F: Float, R: Rng>(sources: &mut Population<F>, trials: &mut [usize], i: usize, data: &Data<F>, metric: Measure, rng: &mut R) {
+    let n_sources = sources.agents.len();
+    let partner = match n_sources {
+        1 => i,
+        _ => rng.gen_range_excluding(0..n_sources, i)
+    };
+
+    let sample = rng.gen_range(0..data.records.nrows());
+    let mut candidate = sources.agents[i].clone();
+
+    {
+        let partner_row = sources.agents[partner].distribution.row(sample);
+        let mut row = candidate.distribution.row_mut(sample);
+
+        for (x, &p) in row.iter_mut().zip(partner_row.iter()) {
+            let phi = F::cast(rng.gen_range(-1.0..=1.0));
+            *x += phi * (*x - p);
+        }
+    }
+
+    let fitness = candidate.fitness_with(data, &metric);
+
+    if fitness > sources.fitness[i] {
+        sources.agents[i] = candidate;
+        sources.fitness[i] = fitness;
+        trials[i] = 0;
+    } else {
+        trials[i] += 1;
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    runner::with_history(|history| run(data, params, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    params.validate()?;
+
+    let n_samples = data.records.nrows();
+    let Parameters { n_classes, n_agents, abandonment_limit, metric, .. } = params;
+
+    let mut trials = vec![0; n_agents];
+
+    runner::run(data, params.settings(), history, |sources, _, rng| {
+        // Employed bees
+        for i in 0..n_agents {
+            explore(sources, &mut trials, i, data, metric, rng);
+        }
+
+        // Onlooker bees pick sources proportionally to their fitness
+        let weights = sources.fitness.iter().map(|f| f.to_f64().unwrap_or(0.0).max(0.0));
+        match WeightedIndex::new(weights) {
+            Ok(roulette) => for _ in 0..n_agents {
+                let i = roulette.sample(rng);
+                explore(sources, &mut trials, i, data, metric, rng);
+            },
+            Err(_) => for i in 0..n_agents {
+                explore(sources, &mut trials, i, data, metric, rng);
+            }
+        }
+
+        // Scout bees abandon exhausted sources; the best one is kept by the runner
+        for (i, trial) in trials.iter_mut().enumerate() {
+            if *trial > abandonment_limit {
+                sources.agents[i] = Fuzzy::random_using(n_samples, n_classes, rng);
+                sources.evaluate(i, data, metric);
+                *trial = 0;
+            }
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,186 @@
+use std::cmp::Ordering;
+
+use linfa::{Float, ParamGuard};
+use rand::Rng;
+
+use super::{distance::Measure, history::History, initialization::Initialization, memetic::Refinement, mutation::levy_flight, runner::{self, Settings}, solution::Fuzzy};
+use crate::{error::ClusteringError, utility::sampling::{ExtendedRng, Levy}, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub discovery_rate: f64,
+    pub step_scale: f64,
+    pub levy_exponent: f64,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn builder(n_classes: usize) -> ParametersBuilder {
+        ParametersBuilder(Parameters {
+            n_classes,
+            n_agents: 20,
+            max_iterations: 500,
+            discovery_rate: 0.25,
+            step_scale: 0.01,
+            levy_exponent: 1.5,
+            metric: Measure::Euclidean,
+            initialization: Initialization::Random,
+            refinement: Refinement::None,
+            seed: None
+        })
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            n_classes: self.n_classes,
+            n_agents: self.n_agents,
+            max_iterations: self.max_iterations,
+            metric: self.metric,
+            initialization: self.initialization,
+            refinement: self.refinement,
+            seed: self.seed
+        }
+    }
+
+    // The biased random walk of a discovered nest needs two other distinct nests
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        if !(0.0..=1.0).contains(&self.discovery_rate) {
+            return Err(ClusteringError::InvalidParameters(format!("discovery rate must lie in [0, 1], got {}", self.discovery_rate)));
+        }
+
+        if !(self.step_scale.is_finite() && self.step_scale >= 0.0) {
+            return Err(ClusteringError::InvalidParameters(format!("step scale must be finite and non-negative, got {}", self.step_scale)));
+        }
+
+        Levy::new(self.levy_exponent)?;
+
+        self.settings().validate(3)
+    }
+}
+
+// Unchecked parameters with defaults, turned into Parameters by ParamGuard::check
+#[derive(Debug, Clone, Copy)]
+pub struct ParametersBuilder(Parameters);
+
+impl ParametersBuilder {
+    pub fn n_agents(mut self, n_agents: usize) -> Self {
+        self.0.n_agents = n_agents;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.0.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn discovery_rate(mut self, discovery_rate: f64) -> Self {
+        self.0.discovery_rate = discovery_rate;
+        self
+    }
+
+    pub fn step_scale(mut self, step_scale: f64) -> Self {
+        self.0.step_scale = step_scale;
+        self
+    }
+
+    pub fn levy_exponent(mut self, levy_exponent: f64) -> Self {
+        self.0.levy_exponent = levy_exponent;
+        self
+    }
+
+    pub fn metric(mut self, metric: Measure) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    pub fn initialization(mut self, initialization: Initialization) -> Self {
+        self.0.initialization = initialization;
+        self
+    }
+
+    pub fn refinement(mut self, refinement: Refinement) -> Self {
+        self.0.refinement = refinement;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.0.seed = Some(seed);
+        self
+    }
+}
+
+impl ParamGuard for ParametersBuilder {
+    type Checked = Parameters;
+    type Error = ClusteringError;
+
+    fn check_ref(&self) -> Result<&Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(&self.0)
+    }
+
+    fn check(self) -> Result<Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    runner::with_history(|history| run(data, params, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    params.validate()?;
+
+    let Parameters { n_agents, discovery_rate, step_scale, levy_exponent, metric, .. } = params;
+
+    let levy = Levy::new(levy_exponent)?;
+    let n_discovered = ((discovery_rate * n_agents as f64) as usize).min(n_agents - 1);
+
+    runner::run(data, params.settings(), history, |nests, _, rng| {
+        let best = nests.best.distribution.clone();
+
+        // Cuckoos lay eggs found by Lévy flights and replace a random nest if fitter
+        for i in 0..n_agents {
+            let mut egg = nests.agents[i].clone();
+            egg.distribution = levy_flight(&egg.distribution, &best, &levy, step_scale, rng);
+
+            let egg_fitness = egg.fitness_with(data, &metric);
+            let j = rng.gen_range(0..n_agents);
+
+            if egg_fitness > nests.fitness[j] {
+                nests.agents[j] = egg;
+                nests.fitness[j] = egg_fitness;
+            }
+        }
+
+        // A fraction of the worst nests is discovered and rebuilt by a biased random walk
+        let mut order = (0..n_agents).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| nests.fitness[a].partial_cmp(&nests.fitness[b]).unwrap_or(Ordering::Equal));
+
+        for &i in order.iter().take(n_discovered) {
+            let (p, q) = loop {
+                let p = rng.gen_range_excluding(0..n_agents, i);
+                let q = rng.gen_range_excluding(0..n_agents, i);
+                if p != q {
+                    break (p, q);
+                }
+            };
+
+            let step = (&nests.agents[p].distribution - &nests.agents[q].distribution) * F::cast(rng.gen::<f64>());
+            nests.agents[i].distribution += &step;
+            nests.evaluate(i, data, metric);
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,33 @@
+use linfa::{prelude::Transformer, Float, ParamGuard};
+use linfa_clustering::Dbscan;
+use ndarray::Array1;
+
+use crate::{error::ClusteringError, preprocessing::missing, Data};
+
+use super::solution::Discrete;
+
+// Noise points are gathered in one extra class after the discovered clusters
+pub fn fit<F: Float>(data: &Data<F>, min_points: usize, tolerance: f64) -> Result<Discrete, ClusteringError> {
+    missing::check(data)?;
+
+    let memberships = Dbscan::params(min_points)
+        .tolerance(F::cast(tolerance))
+        .check()
+        .map_err(|error| ClusteringError::InvalidParameters(error.to_string()))?
+        .transform(&data.records);
+
+    let n_clusters = memberships
+        .iter()
+        .flatten()
+        .max()
+        .map_or(0, |&c| c + 1);
+
+    let has_noise = memberships.iter().any(Option::is_none);
+
+    let indicators = memberships
+        .iter()
+        .map(|membership| membership.unwrap_or(n_clusters))
+        .collect::<Array1<usize>>();
+
+    Ok(Discrete::from_prediction(indicators, n_clusters + has_noise as usize))
+}
@@ -0,0 +1,194 @@
+use linfa::Float;
+use ndarray::{Array2, Axis};
+use ndarray_stats::{errors::{MultiInputError, ShapeMismatch}, DeviationExt, QuantileExt};
+
+use crate::error::ClusteringError;
+
+// Distances between agents, i.e. between membership matrices of equal shape.
+// Every variant is a pseudometric: non-negative, symmetric, zero on identical
+// agents and satisfying the triangle inequality.
+#[derive(Debug, Clone, Copy)]
+pub enum Distance {
+    // Angle between the flattened matrices scaled to [0, 1]; unlike 1 - cos
+    // it satisfies the triangle inequality
+    Cosine,
+    L1,
+    L2,
+    LInf,
+    // Fraction of samples whose most likely cluster differs
+    Hamming
+}
+
+impl Distance {
+    pub fn measure<F: Float>(&self, a: &Array2<F>, b: &Array2<F>) -> Result<F, ClusteringError> {
+        let distance = match self {
+            Distance::Cosine => cosine(a, b)?,
+            Distance::L1 => a.l1_dist(b)?,
+            Distance::L2 => F::cast(a.l2_dist(b)?),
+            Distance::LInf => a.linf_dist(b)?,
+            Distance::Hamming => hamming(a, b)?
+        };
+
+        Ok(distance)
+    }
+}
+
+fn check_shapes<F: Float>(a: &Array2<F>, b: &Array2<F>) -> Result<(), MultiInputError> {
+    if a.shape() != b.shape() {
+        return Err(MultiInputError::ShapeMismatch(ShapeMismatch {
+            first_shape: a.shape().to_vec(),
+            second_shape: b.shape().to_vec()
+        }));
+    }
+
+    if a.is_empty() {
+        return Err(MultiInputError::EmptyInput);
+    }
+
+    Ok(())
+}
+
+fn cosine<F: Float>(a: &Array2<F>, b: &Array2<F>) -> Result<F, MultiInputError> {
+    check_shapes(a, b)?;
+
+    let a_norm = a.iter().map(|&x| x * x).sum::<F>().sqrt();
+    let b_norm = b.iter().map(|&x| x * x).sum::<F>().sqrt();
+
+    // A zero matrix has no direction, it is treated as orthogonal to everything else
+    match (a_norm > F::zero(), b_norm > F::zero()) {
+        (false, false) => return Ok(F::zero()),
+        (true, false) | (false, true) => return Ok(F::cast(0.5)),
+        _ => {}
+    }
+
+    // Numerically stable angle between unit vectors u and v: 2 atan(|u - v| / |u + v|)
+    let (difference, sum) = a
+        .iter()
+        .zip(b.iter())
+        .map(|(&x, &y)| (x / a_norm, y / b_norm))
+        .fold((F::zero(), F::zero()), |(d, s), (u, v)| (d + (u - v) * (u - v), s + (u + v) * (u + v)));
+
+    let angle = F::cast(2.0) * difference.sqrt().atan2(sum.sqrt());
+
+    Ok(angle / F::cast(std::f64::consts::PI))
+}
+
+fn hamming<F: Float>(a: &Array2<F>, b: &Array2<F>) -> Result<F, MultiInputError> {
+    check_shapes(a, b)?;
+
+    let labels = |x: &Array2<F>| x.map_axis(Axis(1), |row| row.argmax().unwrap_or(0));
+
+    let different = labels(a)
+        .iter()
+        .zip(labels(b).iter())
+        .filter(|(x, y)| x != y)
+        .count();
+
+    Ok(F::cast(different) / F::cast(a.nrows()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use ndarray::array;
+    use proptest::{collection::vec, prelude::*};
+
+    const ALL: [Distance; 5] = [
+        Distance::Cosine,
+        Distance::L1,
+        Distance::L2,
+        Distance::LInf,
+        Distance::Hamming
+    ];
+
+    const SLACK: f64 = 1e-9;
+
+    fn agents(n_agents: usize) -> impl Strategy<Value = Vec<Array2<f64>>> {
+        (1..8usize, 1..5usize).prop_flat_map(move |(n_samples, n_classes)| {
+            vec(vec(-1.0..1.0f64, n_samples * n_classes), n_agents).prop_map(move |agents| agents
+                .into_iter()
+                .map(|values| Array2::from_shape_vec((n_samples, n_classes), values).unwrap())
+                .collect()
+            )
+        })
+    }
+
+    proptest! {
+        #[test]
+        fn identity(agents in agents(1)) {
+            for distance in ALL {
+                let d = distance.measure(&agents[0], &agents[0]).unwrap();
+                prop_assert!(d.abs() < SLACK, "{:?}: d(x, x) = {}", distance, d);
+            }
+        }
+
+        #[test]
+        fn non_negativity_and_symmetry(agents in agents(2)) {
+            for distance in ALL {
+                let ab = distance.measure(&agents[0], &agents[1]).unwrap();
+                let ba = distance.measure(&agents[1], &agents[0]).unwrap();
+
+                prop_assert!(ab >= 0.0, "{:?}: negative distance {}", distance, ab);
+                prop_assert!((ab - ba).abs() < SLACK, "{:?}: {} != {}", distance, ab, ba);
+            }
+        }
+
+        #[test]
+        fn triangle_inequality(agents in agents(3)) {
+            let (a, b, c) = (&agents[0], &agents[1], &agents[2]);
+
+            for distance in ALL {
+                let ac = distance.measure(a, c).unwrap();
+                let ab = distance.measure(a, b).unwrap();
+                let bc = distance.measure(b, c).unwrap();
+
+                prop_assert!(ac <= ab + bc + SLACK, "{:?}: {} > {} + {}", distance, ac, ab, bc);
+            }
+        }
+
+        #[test]
+        fn bounded(agents in agents(2)) {
+            for distance in [Distance::Cosine, Distance::Hamming] {
+                let d = distance.measure(&agents[0], &agents[1]).unwrap();
+                prop_assert!(d <= 1.0 + SLACK, "{:?}: {} > 1", distance, d);
+            }
+        }
+    }
+
+    #[test]
+    fn known_values() {
+        let a: Array2<f64> = array![[1.0, 0.0], [0.0, 1.0]];
+        let b: Array2<f64> = array![[0.0, 1.0], [0.0, 1.0]];
+
+        assert!((Distance::L1.measure(&a, &b).unwrap() - 2.0).abs() < SLACK);
+        assert!((Distance::L2.measure(&a, &b).unwrap() - 2f64.sqrt()).abs() < SLACK);
+        assert!((Distance::LInf.measure(&a, &b).unwrap() - 1.0).abs() < SLACK);
+        assert!((Distance::Hamming.measure(&a, &b).unwrap() - 0.5).abs() < SLACK);
+        assert!((Distance::Cosine.measure(&a, &b).unwrap() - 1.0 / 3.0).abs() < SLACK);
+        assert!(Distance::Cosine.measure(&a, &(&a * 3.0)).unwrap().abs() < SLACK);
+    }
+
+    #[test]
+    fn single_precision() {
+        let a: Array2<f64> = array![[0.2, 0.7, 0.1], [0.5, 0.4, 0.9]];
+        let b: Array2<f64> = array![[0.6, 0.3, 0.3], [0.1, 0.8, 0.2]];
+
+        for distance in ALL {
+            let expected = distance.measure(&a, &b).unwrap();
+            let actual = distance.measure(&a.mapv(|x| x as f32), &b.mapv(|x| x as f32)).unwrap();
+
+            assert!((actual as f64 - expected).abs() < 1e-6);
+        }
+    }
+
+    #[test]
+    fn shape_mismatch() {
+        let a = Array2::<f64>::zeros((2, 3));
+        let b = Array2::<f64>::zeros((3, 2));
+
+        for distance in ALL {
+            assert!(distance.measure(&a, &b).is_err());
+        }
+    }
+}
@@ -0,0 +1,335 @@
+pub mod agent;
+
+
+use linfa::Float;
+use ndarray::{Array1, Array2, ArrayView1, ArrayView2, Axis};
+
+use crate::{error::ClusteringError, preprocessing::missing, utility::linalg::invert, Data};
+
+// Dissimilarity between two records (or a record and a centroid) in data space
+pub trait Metric<F: Float = f64> {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F;
+
+    // Representative of a cluster of records used by Lloyd updates, e.g. the
+    // mean or the median; None when the metric has no closed form for it
+    fn center(&self, _records: ArrayView2<F>) -> Option<Array1<F>> {
+        None
+    }
+
+    // Whether the distance is already a squared norm, so that callers
+    // weighting by squared distances must not square it again
+    fn is_squared(&self) -> bool {
+        false
+    }
+}
+
+fn mean<F: Float>(records: ArrayView2<F>) -> Option<Array1<F>> {
+    records.mean_axis(Axis(0))
+}
+
+fn median<F: Float>(records: ArrayView2<F>) -> Option<Array1<F>> {
+    if records.nrows() == 0 {
+        return None;
+    }
+
+    let median = records.map_axis(Axis(0), |column| {
+        let mut values = column.to_vec();
+        values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(std::cmp::Ordering::Equal));
+
+        let middle = values.len() / 2;
+        match values.len() % 2 {
+            0 => (values[middle - 1] + values[middle]) / F::cast(2.0),
+            _ => values[middle]
+        }
+    });
+
+    Some(median)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Euclidean;
+
+#[derive(Debug, Clone, Copy)]
+pub struct SquaredEuclidean;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Manhattan;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Chebyshev;
+
+#[derive(Debug, Clone, Copy)]
+pub struct Minkowski {
+    pub p: f64
+}
+
+// 1 - cosine similarity, in [0, 2]
+#[derive(Debug, Clone, Copy)]
+pub struct Cosine;
+
+// 1 - Pearson correlation, in [0, 2]
+#[derive(Debug, Clone, Copy)]
+pub struct Correlation;
+
+// Euclidean distance over the coordinates present in both records, scaled up
+// by the fraction of missing ones; infinite when no coordinate is shared
+#[derive(Debug, Clone, Copy)]
+pub struct NanEuclidean;
+
+#[derive(Debug, Clone)]
+pub struct Mahalanobis<F = f64> {
+    pub precision: Array2<F>
+}
+
+impl<F: Float> Metric<F> for Euclidean {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        SquaredEuclidean.distance(a, b).sqrt()
+    }
+
+    fn center(&self, records: ArrayView2<F>) -> Option<Array1<F>> {
+        mean(records)
+    }
+}
+
+impl<F: Float> Metric<F> for SquaredEuclidean {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        a.iter().zip(b.iter()).map(|(&x, &y)| (x - y) * (x - y)).sum()
+    }
+
+    fn center(&self, records: ArrayView2<F>) -> Option<Array1<F>> {
+        mean(records)
+    }
+
+    fn is_squared(&self) -> bool {
+        true
+    }
+}
+
+impl<F: Float> Metric<F> for Manhattan {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        a.iter().zip(b.iter()).map(|(&x, &y)| (x - y).abs()).sum()
+    }
+
+    // The coordinate-wise median minimizes the summed L1 distance
+    fn center(&self, records: ArrayView2<F>) -> Option<Array1<F>> {
+        median(records)
+    }
+}
+
+impl<F: Float> Metric<F> for Chebyshev {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        a.iter().zip(b.iter()).map(|(&x, &y)| (x - y).abs()).fold(F::zero(), F::max)
+    }
+}
+
+impl<F: Float> Metric<F> for Minkowski {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        let p = F::cast(self.p);
+
+        a.iter()
+            .zip(b.iter())
+            .map(|(&x, &y)| (x - y).abs().powf(p))
+            .sum::<F>()
+            .powf(p.recip())
+    }
+
+    fn center(&self, records: ArrayView2<F>) -> Option<Array1<F>> {
+        match self.p {
+            1.0 => median(records),
+            2.0 => mean(records),
+            _ => None
+        }
+    }
+}
+
+impl<F: Float> Metric<F> for Cosine {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        let norms = a.dot(&a).sqrt() * b.dot(&b).sqrt();
+
+        match norms > F::zero() {
+            true => F::one() - a.dot(&b) / norms,
+            false => F::one()
+        }
+    }
+
+    // Mean of the records scaled to unit length, as in spherical k-means
+    fn center(&self, records: ArrayView2<F>) -> Option<Array1<F>> {
+        let mut unit = records.to_owned();
+
+        for mut record in unit.axis_iter_mut(Axis(0)) {
+            let norm = record.dot(&record).sqrt();
+
+            if norm > F::zero() {
+                record.mapv_inplace(|x| x / norm);
+            }
+        }
+
+        mean(unit.view())
+    }
+}
+
+impl<F: Float> Metric<F> for Correlation {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        let a = &a - a.mean().unwrap_or_else(F::zero);
+        let b = &b - b.mean().unwrap_or_else(F::zero);
+
+        Cosine.distance(a.view(), b.view())
+    }
+}
+
+impl<F: Float> Metric<F> for NanEuclidean {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        let (total, present) = a
+            .iter()
+            .zip(b.iter())
+            .filter(|(x, y)| !x.is_nan() && !y.is_nan())
+            .fold((F::zero(), 0usize), |(total, present), (&x, &y)| (total + (x - y) * (x - y), present + 1));
+
+        match present {
+            0 => F::infinity(),
+            _ => (total * F::cast(a.len()) / F::cast(present)).sqrt()
+        }
+    }
+
+    // Mean of the observed values of each feature, NaN where none is observed
+    fn center(&self, records: ArrayView2<F>) -> Option<Array1<F>> {
+        if records.nrows() == 0 {
+            return None;
+        }
+
+        let center = records.map_axis(Axis(0), |column| {
+            let (total, present) = column
+                .iter()
+                .filter(|x| !x.is_nan())
+                .fold((F::zero(), 0usize), |(total, present), &x| (total + x, present + 1));
+
+            match present {
+                0 => F::nan(),
+                _ => total / F::cast(present)
+            }
+        });
+
+        Some(center)
+    }
+}
+
+impl<F: Float> Mahalanobis<F> {
+    // Uses the inverse of the sample covariance of the records
+    pub fn fit(data: &Data<F>) -> Result<Self, ClusteringError> {
+        missing::check(data)?;
+
+        let records = data.records();
+        let n_samples = records.nrows();
+
+        if n_samples < 2 {
+            return Err(ClusteringError::InvalidParameters("Mahalanobis distance needs at least two samples".into()));
+        }
+
+        let mean = records.mean_axis(Axis(0)).ok_or_else(|| ClusteringError::InvalidParameters("empty dataset".into()))?;
+        let deviations = records - &mean;
+        let covariance = deviations.t().dot(&deviations) / F::cast(n_samples - 1);
+
+        let (precision, _) = invert(&covariance).ok_or_else(|| ClusteringError::Numerical("singular covariance matrix".into()))?;
+
+        Ok(Mahalanobis { precision })
+    }
+}
+
+impl<F: Float> Metric<F> for Mahalanobis<F> {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        let difference = &a - &b;
+
+        difference.dot(&self.precision.dot(&difference)).max(F::zero()).sqrt()
+    }
+
+    fn center(&self, records: ArrayView2<F>) -> Option<Array1<F>> {
+        mean(records)
+    }
+}
+
+// Metrics that need no fitting, so that they can be chosen in the Copy
+// parameters of the optimizers; Mahalanobis is estimated from the data and
+// goes through the generic _with functions instead
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Measure {
+    Euclidean,
+    SquaredEuclidean,
+    Manhattan,
+    Chebyshev,
+    Minkowski { p: f64 },
+    Cosine,
+    Correlation,
+    NanEuclidean
+}
+
+impl Measure {
+    // Whether Lloyd iterations, and so memetic refinement, can run under it
+    pub fn has_center(&self) -> bool {
+        match *self {
+            Measure::Euclidean | Measure::SquaredEuclidean | Measure::Manhattan | Measure::Cosine | Measure::NanEuclidean => true,
+            Measure::Minkowski { p } => p == 1.0 || p == 2.0,
+            Measure::Chebyshev | Measure::Correlation => false
+        }
+    }
+
+    // Only NaN-aware distances can be fitted on records with missing values
+    pub fn accepts_missing(&self) -> bool {
+        matches!(self, Measure::NanEuclidean)
+    }
+
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        match *self {
+            Measure::Minkowski { p } if !(p.is_finite() && p >= 1.0) => Err(ClusteringError::InvalidParameters(format!(
+                "Minkowski distance needs a finite p >= 1, got {}",
+                p
+            ))),
+            _ => Ok(())
+        }
+    }
+}
+
+impl<F: Float> Metric<F> for Measure {
+    fn distance(&self, a: ArrayView1<F>, b: ArrayView1<F>) -> F {
+        match *self {
+            Measure::Euclidean => Euclidean.distance(a, b),
+            Measure::SquaredEuclidean => SquaredEuclidean.distance(a, b),
+            Measure::Manhattan => Manhattan.distance(a, b),
+            Measure::Chebyshev => Chebyshev.distance(a, b),
+            Measure::Minkowski { p } => Minkowski { p }.distance(a, b),
+            Measure::Cosine => Cosine.distance(a, b),
+            Measure::Correlation => Correlation.distance(a, b),
+            Measure::NanEuclidean => NanEuclidean.distance(a, b)
+        }
+    }
+
+    fn center(&self, records: ArrayView2<F>) -> Option<Array1<F>> {
+        match *self {
+            Measure::Euclidean => Euclidean.center(records),
+            Measure::SquaredEuclidean => SquaredEuclidean.center(records),
+            Measure::Manhattan => Manhattan.center(records),
+            Measure::Chebyshev => Chebyshev.center(records),
+            Measure::Minkowski { p } => Minkowski { p }.center(records),
+            Measure::Cosine => Cosine.center(records),
+            Measure::Correlation => Correlation.center(records),
+            Measure::NanEuclidean => NanEuclidean.center(records)
+        }
+    }
+
+    fn is_squared(&self) -> bool {
+        matches!(self, Measure::SquaredEuclidean)
+    }
+}
+
+// Distances from every record to every centroid
+pub fn to_centroids<F: Float, M: Metric<F> + ?Sized>(data: &Data<F>, centroids: &Array2<F>, metric: &M) -> Array2<F> {
+    let samples = data.records();
+    let mut distances = Array2::<F>::zeros((samples.nrows(), centroids.nrows()));
+
+    for (mut row, record) in distances.axis_iter_mut(Axis(0)).zip(samples.axis_iter(Axis(0))) {
+        for (distance, centroid) in row.iter_mut().zip(centroids.axis_iter(Axis(0))) {
+            *distance = metric.distance(record, centroid);
+        }
+    }
+
+    distances
+}
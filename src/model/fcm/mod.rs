@@ -0,0 +1,213 @@
+use linfa::Float;
+use ndarray::{Array1, Array2, Axis};
+use ndarray_rand::{rand_distr::Uniform, RandomExt};
+
+use super::{history::History, initialization, solution::Probabilistic};
+use crate::{error::ClusteringError, preprocessing::missing, utility::{linalg::invert, sampling::seeded_rng}, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Variant {
+    Standard,
+    // Gustafson & Kessel (1979): adaptive Mahalanobis norm per cluster with unit volume
+    GustafsonKessel
+}
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub fuzzifier: f64,
+    pub max_iterations: usize,
+    pub tolerance: f64,
+    pub variant: Variant,
+    pub seed: Option<u64>
+}
+
+const TOLERANCE: f64 = 1e-12;
+
+// Cluster prototypes weighted by memberships raised to the fuzzifier
+pub fn centroids<F: Float>(data: &Data<F>, memberships: &Probabilistic<F>, fuzzifier: f64) -> Array2<F> {
+    let weights = memberships.distribution.mapv(|u| u.powf(F::cast(fuzzifier)));
+    let totals = weights.sum_axis(Axis(0)).mapv(|w| w.max(F::cast(TOLERANCE)));
+
+    weights.t().dot(&data.records) / &totals.insert_axis(Axis(1))
+}
+
+fn squared_distances<F: Float>(
+    data: &Data<F>,
+    memberships: &Probabilistic<F>,
+    centroids: &Array2<F>,
+    fuzzifier: f64,
+    variant: Variant
+) -> Result<Array2<F>, ClusteringError> {
+    let records = &data.records;
+    let n_features = records.ncols();
+    let mut distances = Array2::<F>::zeros((records.nrows(), centroids.nrows()));
+
+    for (k, centroid) in centroids.axis_iter(Axis(0)).enumerate() {
+        let deviations = records - &centroid;
+
+        let norm = match variant {
+            Variant::Standard => Array2::<F>::eye(n_features),
+            Variant::GustafsonKessel => {
+                let weights = memberships.distribution.column(k).mapv(|u| u.powf(F::cast(fuzzifier)));
+                let weighted = &deviations * &weights.clone().insert_axis(Axis(1));
+                let covariance = weighted.t().dot(&deviations) / weights.sum().max(F::cast(TOLERANCE))
+                    + Array2::<F>::eye(n_features) * F::cast(TOLERANCE);
+
+                let (inverse, determinant) = invert(&covariance)
+                    .ok_or_else(|| ClusteringError::Numerical("singular fuzzy covariance matrix".into()))?;
+
+                inverse * determinant.abs().powf(F::cast(n_features).recip())
+            }
+        };
+
+        let projected = deviations.dot(&norm);
+        let column = (&projected * &deviations).sum_axis(Axis(1));
+
+        distances.column_mut(k).assign(&column);
+    }
+
+    Ok(distances)
+}
+
+fn update_memberships<F: Float>(distances: &Array2<F>, fuzzifier: f64) -> Array2<F> {
+    let exponent = F::cast(1.0 / (fuzzifier - 1.0));
+    let mut memberships = distances.mapv(|d| d.max(F::cast(TOLERANCE)).powf(-exponent));
+
+    for mut row in memberships.axis_iter_mut(Axis(0)) {
+        let total = row.sum();
+        row.mapv_inplace(|u| u / total);
+    }
+
+    memberships
+}
+
+pub fn objective<F: Float>(data: &Data<F>, memberships: &Probabilistic<F>, fuzzifier: f64, variant: Variant) -> Result<F, ClusteringError> {
+    let centroids = centroids(data, memberships, fuzzifier);
+    let distances = squared_distances(data, memberships, &centroids, fuzzifier, variant)?;
+
+    Ok((memberships.distribution.mapv(|u| u.powf(F::cast(fuzzifier))) * distances).sum())
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Probabilistic<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+// Also returns the fitness of the current memberships after every iteration
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Probabilistic<F>, History), ClusteringError> {
+    let mut history = History::new();
+    let best = run(data, params, Some(&mut history))?;
+
+    Ok((best, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, mut history: Option<&mut History>) -> Result<Probabilistic<F>, ClusteringError> {
+    missing::check(data)?;
+
+    let Parameters {
+        n_classes,
+        fuzzifier,
+        max_iterations,
+        tolerance,
+        variant,
+        seed
+    } = params;
+
+    initialization::check_classes(data, n_classes)?;
+
+    if !fuzzifier.is_finite() || fuzzifier <= 1.0 {
+        return Err(ClusteringError::InvalidParameters("fuzzifier must be finite and greater than 1".into()));
+    }
+
+    if !tolerance.is_finite() || tolerance < 0.0 {
+        return Err(ClusteringError::InvalidParameters("tolerance must be finite and non-negative".into()));
+    }
+
+    let n_samples = data.records.nrows();
+    let mut rng = seeded_rng(seed);
+
+    let mut distribution = Array2::random_using((n_samples, n_classes), Uniform::new(F::zero(), F::one()), &mut rng);
+    let totals: Array1<F> = distribution.sum_axis(Axis(1));
+    distribution /= &totals.insert_axis(Axis(1));
+
+    let mut memberships = Probabilistic { distribution, n_samples, n_classes };
+
+    for _ in 0..max_iterations {
+        let centroids = centroids(data, &memberships, fuzzifier);
+        let distances = squared_distances(data, &memberships, &centroids, fuzzifier, variant)?;
+        let updated = update_memberships(&distances, fuzzifier);
+
+        let change = (&updated - &memberships.distribution)
+            .mapv(|x| x.abs())
+            .fold(F::zero(), |a, &b| a.max(b));
+
+        memberships.distribution = updated;
+
+        if let Some(history) = history.as_deref_mut() {
+            history.record(&[memberships.clone().to_discrete()?.fitness(data)]);
+        }
+
+        if change < F::cast(tolerance) {
+            break;
+        }
+    }
+
+    Ok(memberships)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::{metric, solution::Discrete};
+
+    fn params() -> Parameters {
+        Parameters {
+            n_classes: 3,
+            fuzzifier: 2.0,
+            max_iterations: 20,
+            tolerance: 1e-6,
+            variant: Variant::Standard,
+            seed: Some(0)
+        }
+    }
+
+    #[test]
+    fn rejects_zero_classes() {
+        let data = linfa_datasets::iris();
+
+        assert!(fit_with_history(&data, Parameters { n_classes: 0, ..params() }).is_err());
+    }
+
+    #[test]
+    fn rejects_non_finite_fuzzifier() {
+        let data = linfa_datasets::iris();
+
+        for fuzzifier in [f64::NAN, f64::INFINITY, 1.0] {
+            assert!(fit(&data, Parameters { fuzzifier, ..params() }).is_err(), "accepted fuzzifier {}", fuzzifier);
+        }
+    }
+
+    #[test]
+    fn rejects_non_finite_tolerance() {
+        let data = linfa_datasets::iris();
+
+        for tolerance in [f64::NAN, f64::INFINITY, -1.0] {
+            assert!(fit(&data, Parameters { tolerance, ..params() }).is_err(), "accepted tolerance {}", tolerance);
+        }
+    }
+
+    #[test]
+    fn single_precision() {
+        let data = linfa_datasets::iris();
+        let data32 = linfa::DatasetBase::new(data.records.mapv(|x| x as f32), data.targets.clone());
+        let truth = Discrete::new(&data);
+
+        let accuracy = |memberships: Discrete| metric::accuracy(&truth, &memberships.matched_with(&truth).unwrap()).unwrap();
+
+        let expected = fit(&data, params()).unwrap();
+        let actual = fit(&data32, params()).unwrap();
+
+        assert!(actual.distribution.iter().all(|x| x.is_finite()));
+        assert!((accuracy(actual.to_discrete().unwrap()) - accuracy(expected.to_discrete().unwrap())).abs() < 0.02);
+    }
+}
@@ -0,0 +1,199 @@
+use linfa::{Float, ParamGuard};
+use ndarray::Array2;
+use ndarray_rand::{rand_distr::Uniform, RandomExt};
+
+use super::{distance::Measure, history::History, initialization::Initialization, memetic::Refinement, runner::{self, Settings}, solution::Fuzzy};
+use crate::{error::ClusteringError, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub attractiveness: f64,
+    pub absorption: f64,
+    pub randomness: f64,
+    pub randomness_decay: f64,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn builder(n_classes: usize) -> ParametersBuilder {
+        ParametersBuilder(Parameters {
+            n_classes,
+            n_agents: 20,
+            max_iterations: 500,
+            attractiveness: 1.0,
+            absorption: 1.0,
+            randomness: 0.2,
+            randomness_decay: 0.97,
+            metric: Measure::Euclidean,
+            initialization: Initialization::Random,
+            refinement: Refinement::None,
+            seed: None
+        })
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            n_classes: self.n_classes,
+            n_agents: self.n_agents,
+            max_iterations: self.max_iterations,
+            metric: self.metric,
+            initialization: self.initialization,
+            refinement: self.refinement,
+            seed: self.seed
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        let non_negative = [
+            ("attractiveness", self.attractiveness),
+            ("absorption", self.absorption),
+            ("randomness", self.randomness)
+        ];
+
+        for (name, value) in non_negative {
+            if !(value.is_finite() && value >= 0.0) {
+                return Err(ClusteringError::InvalidParameters(format!("{} must be finite and non-negative, got {}", name, value)));
+            }
+        }
+
+        if !(self.randomness_decay > 0.0 && self.randomness_decay <= 1.0) {
+            return Err(ClusteringError::InvalidParameters(format!("randomness decay must lie in (0, 1], got {}", self.randomness_decay)));
+        }
+
+        self.settings().validate(1)
+    }
+}
+
+// Unchecked parameters with defaults, turned into Parameters by ParamGuard::check
+#[derive(Debug, Clone, Copy)]
+pub struct ParametersBuilder(Parameters);
+
+impl ParametersBuilder {
+    pub fn n_agents(mut self, n_agents: usize) -> Self {
+        self.0.n_agents = n_agents;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.0.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn attractiveness(mut self, attractiveness: f64) -> Self {
+        self.0.attractiveness = attractiveness;
+        self
+    }
+
+    pub fn absorption(mut self, absorption: f64) -> Self {
+        self.0.absorption = absorption;
+        self
+    }
+
+    pub fn randomness(mut self, randomness: f64) -> Self {
+        self.0.randomness = randomness;
+        self
+    }
+
+    pub fn randomness_decay(mut self, randomness_decay: f64) -> Self {
+        self.0.randomness_decay = randomness_decay;
+        self
+    }
+
+    pub fn metric(mut self, metric: Measure) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    pub fn initialization(mut self, initialization: Initialization) -> Self {
+        self.0.initialization = initialization;
+        self
+    }
+
+    pub fn refinement(mut self, refinement: Refinement) -> Self {
+        self.0.refinement = refinement;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.0.seed = Some(seed);
+        self
+    }
+}
+
+impl ParamGuard for ParametersBuilder {
+    type Checked = Parameters;
+    type Error = ClusteringError;
+
+    fn check_ref(&self) -> Result<&Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(&self.0)
+    }
+
+    fn check(self) -> Result<Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    runner::with_history(|history| run(data, params, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    params.validate()?;
+
+    let n_samples = data.records.nrows();
+
+    let Parameters {
+        n_classes,
+        n_agents,
+        attractiveness,
+        absorption,
+        randomness,
+        randomness_decay,
+        metric,
+        ..
+    } = params;
+
+    let mut randomness = randomness;
+    let n_dimensions = F::cast(n_samples * n_classes);
+
+    // The cached fitness is the light intensity of every firefly
+    runner::run(data, params.settings(), history, |swarm, _, rng| {
+        for i in 0..n_agents {
+            for j in 0..n_agents {
+                if swarm.fitness[j] <= swarm.fitness[i] {
+                    continue;
+                }
+
+                // Squared distance per dimension, so that the absorption
+                // coefficient does not depend on the size of the dataset
+                let distance = (&swarm.agents[j].distribution - &swarm.agents[i].distribution)
+                    .mapv_into(|x| x * x)
+                    .sum() / n_dimensions;
+
+                let beta = F::cast(attractiveness) * (-F::cast(absorption) * distance).exp();
+                let noise = Array2::random_using((n_samples, n_classes), Uniform::new(F::cast(-0.5), F::cast(0.5)), rng);
+
+                let attraction = (&swarm.agents[j].distribution - &swarm.agents[i].distribution) * beta;
+                swarm.agents[i].distribution += &(attraction + noise * F::cast(randomness));
+
+                swarm.evaluate(i, data, metric);
+            }
+        }
+
+        randomness *= randomness_decay;
+
+        Ok(())
+    })
+}
@@ -0,0 +1,65 @@
+use std::f64::consts::PI;
+
+use linfa::{prelude::Fit, Dataset, Float};
+use linfa_clustering::GaussianMixtureModel;
+use ndarray::{Array2, Axis};
+
+use crate::{error::ClusteringError, preprocessing::missing, utility::{linalg::invert, sampling::seeded_rng}, Data};
+
+use super::solution::Probabilistic;
+
+// Posterior responsibilities of every component for every sample
+fn responsibilities<F: Float>(data: &Data<F>, model: &GaussianMixtureModel<F>) -> Result<Array2<F>, ClusteringError> {
+    let records = &data.records;
+    let n_features = F::cast(records.ncols());
+    let n_components = model.weights().len();
+    let half = F::cast(0.5);
+
+    let mut log_prob = Array2::<F>::zeros((records.nrows(), n_components));
+
+    for k in 0..n_components {
+        let precision = model.precisions().index_axis(Axis(0), k).to_owned();
+        let (_, determinant) = invert(&precision).ok_or_else(|| ClusteringError::Numerical("singular precision matrix".into()))?;
+
+        let deviations = records - &model.means().row(k);
+        let mahalanobis = (&deviations.dot(&precision) * &deviations).sum_axis(Axis(1));
+
+        let constant = model.weights()[k].ln()
+            + half * determinant.ln()
+            - half * n_features * F::cast(2.0 * PI).ln();
+
+        log_prob.column_mut(k).assign(&mahalanobis.mapv(|m| constant - half * m));
+    }
+
+    for mut row in log_prob.axis_iter_mut(Axis(0)) {
+        let max = row.fold(F::neg_infinity(), |a, &b| a.max(b));
+        row.mapv_inplace(|x| (x - max).exp());
+        let total = row.sum();
+        row.mapv_inplace(|x| x / total);
+    }
+
+    Ok(log_prob)
+}
+
+pub fn fit<F: Float>(
+    data: &Data<F>,
+    n_clusters: usize,
+    max_iterations: usize,
+    tolerance: f64,
+    seed: Option<u64>
+) -> Result<Probabilistic<F>, ClusteringError> {
+    missing::check(data)?;
+
+    let dataset = Dataset::from(data.records.to_owned());
+
+    let model = GaussianMixtureModel::params_with_rng(n_clusters, seeded_rng(seed))
+        .max_n_iterations(max_iterations as u64)
+        .tolerance(F::cast(tolerance))
+        .fit(&dataset)
+        .map_err(|error| ClusteringError::InvalidParameters(error.to_string()))?;
+
+    let distribution = responsibilities(data, &model)?;
+    let n_samples = distribution.nrows();
+
+    Ok(Probabilistic { distribution, n_samples, n_classes: n_clusters })
+}
@@ -0,0 +1,158 @@
+use linfa::Float;
+use ndarray::{Array1, Array2};
+use rand::Rng;
+
+use super::{gravity, masses, TOLERANCE};
+use crate::{error::ClusteringError, model::{distance::Measure, history::History, solution::Activated}, preprocessing::missing, utility::{order::Ordered, sampling::seeded_rng}, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub k_min: usize,
+    pub k_max: usize,
+    pub threshold: f64,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub metric: Measure,
+    pub initial_gravity: f64,
+    pub gravity_decay: f64,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        Activated::validate(self.k_min, self.k_max, self.threshold)?;
+
+        if self.n_agents < 2 {
+            return Err(ClusteringError::InvalidParameters(format!("GSA needs at least 2 agents, got {}", self.n_agents)));
+        }
+
+        if self.max_iterations == 0 {
+            return Err(ClusteringError::InvalidParameters("number of iterations must be positive".into()));
+        }
+
+        self.metric.validate()?;
+
+        if !(self.initial_gravity.is_finite() && self.initial_gravity > 0.0) {
+            return Err(ClusteringError::InvalidParameters(format!("initial gravity must be finite and positive, got {}", self.initial_gravity)));
+        }
+
+        if !(self.gravity_decay.is_finite() && self.gravity_decay >= 0.0) {
+            return Err(ClusteringError::InvalidParameters(format!("gravity decay must be finite and non-negative, got {}", self.gravity_decay)));
+        }
+
+        Ok(())
+    }
+}
+
+fn distance<F: Float>(a: &Activated<F>, b: &Activated<F>) -> F {
+    let centroids = (&a.centroids - &b.centroids).mapv_into(|x| x * x).sum();
+    let activations = (&a.activations - &b.activations).mapv_into(|x| x * x).sum();
+
+    (centroids + activations).sqrt()
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Activated<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+// Also returns the fitness of the swarm after every iteration
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Activated<F>, History), ClusteringError> {
+    let mut history = History::new();
+    let best = run(data, params, Some(&mut history))?;
+
+    Ok((best, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, mut history: Option<&mut History>) -> Result<Activated<F>, ClusteringError> {
+    params.validate()?;
+    missing::check_with(data, params.metric)?;
+
+    if params.k_min > data.records.nrows() {
+        return Err(ClusteringError::InvalidParameters(format!(
+            "k_min = {} exceeds the number of samples {}",
+            params.k_min,
+            data.records.nrows()
+        )));
+    }
+
+    let Parameters {
+        k_min,
+        k_max,
+        threshold,
+        n_agents,
+        max_iterations,
+        metric,
+        initial_gravity,
+        gravity_decay,
+        seed
+    } = params;
+
+    let n_features = data.records.ncols();
+    let mut rng = seeded_rng(seed);
+
+    let mut agents: Vec<Activated<F>> = (0..n_agents)
+        .map(|_| {
+            let mut agent = Activated::random_using(data, k_max, F::cast(threshold), &mut rng);
+            agent.repair(data, k_min, &mut rng);
+            agent
+        })
+        .collect();
+
+    let mut centroid_velocities = vec![Array2::<F>::zeros((k_max, n_features)); n_agents];
+    let mut activation_velocities = vec![Array1::<F>::zeros(k_max); n_agents];
+
+    let max_time = max_iterations as f64;
+
+    for time in 0..max_iterations {
+        let gravity = gravity(initial_gravity, gravity_decay, time as f64, max_time);
+
+        let fitness = agents
+            .iter()
+            .map(|agent| agent.fitness_with(data, &metric))
+            .collect::<Vec<_>>();
+
+        let masses = masses(&fitness)?;
+
+        for i in 0..n_agents {
+            let mut centroid_force = Array2::<F>::zeros((k_max, n_features));
+            let mut activation_force = Array1::<F>::zeros(k_max);
+
+            for j in (0..n_agents).filter(|&j| j != i) {
+                let scale = F::cast(rng.gen::<f64>() * gravity) * masses[i] * masses[j]
+                    / (distance(&agents[i], &agents[j]) + F::cast(TOLERANCE));
+
+                centroid_force.scaled_add(scale, &(&agents[j].centroids - &agents[i].centroids));
+                activation_force.scaled_add(scale, &(&agents[j].activations - &agents[i].activations));
+            }
+
+            let randomizer = F::cast(rng.gen::<f64>());
+
+            centroid_velocities[i] *= randomizer;
+            centroid_velocities[i].scaled_add(F::one() / masses[i], &centroid_force);
+
+            activation_velocities[i] *= randomizer;
+            activation_velocities[i].scaled_add(F::one() / masses[i], &activation_force);
+        }
+
+        for (i, agent) in agents.iter_mut().enumerate() {
+            agent.centroids += &centroid_velocities[i];
+            agent.activations += &activation_velocities[i];
+            agent.repair(data, k_min, &mut rng);
+        }
+
+        if let Some(history) = history.as_deref_mut() {
+            history.record(&agents.iter().map(|agent| agent.fitness_with(data, &metric)).collect::<Vec<_>>());
+        }
+    }
+
+    let fitness = agents
+        .iter()
+        .map(|agent| agent.fitness_with(data, &metric))
+        .collect::<Vec<_>>();
+
+    let best = fitness
+        .argmax()
+        .ok_or_else(|| ClusteringError::DegeneratePopulation("fitness of the population is empty or NaN".into()))?;
+
+    Ok(agents[best].clone())
+}
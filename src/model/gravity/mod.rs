@@ -1,22 +1,18 @@
-use std::error::Error;
+pub mod dynamic;
+
 
 #[allow(unused_imports)]
 use itertools::{iproduct, Itertools};
 
+use linfa::{Float, ParamGuard};
 use ndarray::{s, Array, Array1, Array2, Array3};
 use ndarray_rand::RandomExt;
-use ndarray_stats::DeviationExt;
-use rand::{distributions::Uniform, random};
+use rand::{distributions::Uniform, Rng};
 
-use super::solution::Fuzzy;
-use crate::{utility::{array::Norm, normalization::Normalize, order::Ordered}, Data};
+use super::{distance::Measure, history::History, initialization::{self, Initialization}, memetic::Refinement, mutation::Mutation, solution::Fuzzy};
+use crate::{error::ClusteringError, utility::{normalization::Normalize, order::Ordered, sampling::seeded_rng}, Data};
 
-#[derive(Debug, Clone, Copy)]
-pub enum Distance {
-    Cosine,
-    L2,
-    LInf
-}
+pub use super::distance::agent::Distance;
 
 #[derive(Debug, Clone, Copy)]
 pub enum Normalization {
@@ -33,86 +29,234 @@ pub struct Parameters {
     pub initial_gravity: f64,
     pub gravity_decay: f64,
     pub distance: Distance,
-    pub normalization: Normalization
+    pub normalization: Normalization,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub mutation: Mutation,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn builder(n_classes: usize) -> ParametersBuilder {
+        ParametersBuilder(Parameters {
+            n_classes,
+            n_agents: 10,
+            max_iterations: 500,
+            initial_gravity: 1.0,
+            gravity_decay: 0.01,
+            distance: Distance::Cosine,
+            normalization: Normalization::MinMax,
+            metric: Measure::Euclidean,
+            initialization: Initialization::Random,
+            refinement: Refinement::None,
+            mutation: Mutation::None,
+            seed: None
+        })
+    }
+
+    // Checks that do not depend on the data; the number of classes is compared
+    // with the number of samples when the population is initialized
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        if self.n_classes == 0 {
+            return Err(ClusteringError::InvalidParameters("number of classes must be positive".into()));
+        }
+
+        // A single agent has no fitness range to derive masses from and no one to attract
+        if self.n_agents < 2 {
+            return Err(ClusteringError::InvalidParameters(format!("GSA needs at least 2 agents, got {}", self.n_agents)));
+        }
+
+        if self.max_iterations == 0 {
+            return Err(ClusteringError::InvalidParameters("number of iterations must be positive".into()));
+        }
+
+        if !(self.initial_gravity.is_finite() && self.initial_gravity > 0.0) {
+            return Err(ClusteringError::InvalidParameters(format!("initial gravity must be finite and positive, got {}", self.initial_gravity)));
+        }
+
+        if !(self.gravity_decay.is_finite() && self.gravity_decay >= 0.0) {
+            return Err(ClusteringError::InvalidParameters(format!("gravity decay must be finite and non-negative, got {}", self.gravity_decay)));
+        }
+
+        self.metric.validate()?;
+        self.refinement.validate(self.metric)?;
+        self.mutation.validate()
+    }
+}
+
+// Unchecked parameters with defaults, turned into Parameters by ParamGuard::check
+#[derive(Debug, Clone, Copy)]
+pub struct ParametersBuilder(Parameters);
+
+impl ParametersBuilder {
+    pub fn n_agents(mut self, n_agents: usize) -> Self {
+        self.0.n_agents = n_agents;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.0.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn initial_gravity(mut self, initial_gravity: f64) -> Self {
+        self.0.initial_gravity = initial_gravity;
+        self
+    }
+
+    pub fn gravity_decay(mut self, gravity_decay: f64) -> Self {
+        self.0.gravity_decay = gravity_decay;
+        self
+    }
+
+    pub fn distance(mut self, distance: Distance) -> Self {
+        self.0.distance = distance;
+        self
+    }
+
+    pub fn normalization(mut self, normalization: Normalization) -> Self {
+        self.0.normalization = normalization;
+        self
+    }
+
+    pub fn metric(mut self, metric: Measure) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    pub fn initialization(mut self, initialization: Initialization) -> Self {
+        self.0.initialization = initialization;
+        self
+    }
+
+    pub fn refinement(mut self, refinement: Refinement) -> Self {
+        self.0.refinement = refinement;
+        self
+    }
+
+    pub fn mutation(mut self, mutation: Mutation) -> Self {
+        self.0.mutation = mutation;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.0.seed = Some(seed);
+        self
+    }
+}
+
+impl ParamGuard for ParametersBuilder {
+    type Checked = Parameters;
+    type Error = ClusteringError;
+
+    fn check_ref(&self) -> Result<&Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(&self.0)
+    }
+
+    fn check(self) -> Result<Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
 }
 
 const TOLERANCE: f64 = 1e-16;
 
-fn fitness(agents: &Vec<Fuzzy>, data: &Data) -> Vec<f64> {
+// Smallest distance between agents, relative to the largest one, used in the force
+const DISTANCE_FLOOR: f64 = 1e-3;
+
+fn fitness<F: Float>(agents: &[Fuzzy<F>], data: &Data<F>, metric: Measure) -> Vec<F> {
     agents
         .iter()
-        .map(|agent| agent.fitness(&data))
+        .map(|agent| agent.fitness_with(data, &metric))
         .collect::<Vec<_>>()
 }
 
-fn masses(fitness: &Vec<f64>) -> Array1<f64> {
-    let (worst, best) = fitness.min_max().unwrap();
+fn masses<F: Float>(fitness: &Vec<F>) -> Result<Array1<F>, ClusteringError> {
+    let (&worst, &best) = fitness
+        .min_max()
+        .ok_or_else(|| ClusteringError::DegeneratePopulation("fitness of the population is empty or NaN".into()))?;
+
+    // A collapsed population has no fitness range to scale masses by
+    if best <= worst {
+        return Ok(Array1::from_elem(fitness.len(), F::cast(fitness.len()).recip()));
+    }
+
     let range = best - worst;
 
     let masses = fitness
         .iter()
-        .map(|f| (f - worst) / range)
+        .map(|&f| (f - worst) / range)
         .collect::<Vec<_>>();
 
-    let total_mass: f64 = masses.iter().sum();
+    let total_mass: F = masses.iter().copied().sum();
 
     let masses = masses
         .iter()
-        .map(|mass| mass / total_mass + TOLERANCE)
+        .map(|&mass| mass / total_mass + F::cast(TOLERANCE))
         .collect::<Vec<_>>();
 
-    Array::from_vec(masses)
+    Ok(Array::from_vec(masses))
 }
 
-#[inline(always)]
-fn cosine_distance(x: &Array2<f64>, y: &Array2<f64>) -> f64 {
-    let x_norm = x.l2();
-    let y_norm = y.l2();
-    let inner = (x * y).sum();
-
-    let similarity = inner / (x_norm * y_norm).sqrt();
-
-    (1.0 - similarity) / 2.0
+// Gravitational constant, agent masses and distance of one iteration
+struct Field<'a, F> {
+    gravity: F,
+    masses: &'a Array1<F>,
+    distance: Distance
 }
 
-fn total_forces(
+fn total_forces<F: Float, R: Rng>(
     n_samples: usize,
     n_classes: usize,
-    n_agents: usize,
-    gravity: f64,
-    masses: &Array1<f64>,
-    agents: &Vec<Fuzzy>,
-    distance: Distance
-) -> Array3<f64> {
-    let mut total_forces = Array3::<f64>::zeros((n_agents, n_samples, n_classes));
+    agents: &[Fuzzy<F>],
+    field: &Field<F>,
+    rng: &mut R
+) -> Result<Array3<F>, ClusteringError> {
+    let Field { gravity, masses, distance } = *field;
+    let n_agents = agents.len();
 
-    let mass_enumerator = iproduct!(
-        masses.iter().enumerate(),
-        masses.iter().enumerate()
-    ).filter(|((i, _), (j, _))| i != j);
+    let mut total_forces = Array3::<F>::zeros((n_agents, n_samples, n_classes));
 
-    for ((i, &mass_i), (j, &mass_j)) in mass_enumerator {
-        let x_i: &Array2<f64> = &agents[i].distribution;
-        let x_j: &Array2<f64> = &agents[j].distribution;
+    let mut distances = Array2::<F>::zeros((n_agents, n_agents));
 
-        let difference = x_j - x_i;
+    for (i, j) in iproduct!(0..n_agents, 0..n_agents).filter(|(i, j)| i < j) {
+        let d = distance.measure(&agents[i].distribution, &agents[j].distribution)?;
+        distances[[i, j]] = d;
+        distances[[j, i]] = d;
+    }
 
-        let distance = match distance {
-            Distance::Cosine => cosine_distance(x_i, x_j),
-            Distance::L2 => x_i.l2_dist(x_j).unwrap(),
-            Distance::LInf => x_i.linf_dist(x_j).unwrap()
-        };
+    // Cosine and Hamming are pseudometrics: distinct agents can lie at distance
+    // zero. Such pairs exert no force and the remaining distances are floored
+    // relative to the spread of the swarm, so that no pair dominates it.
+    let scale = distances.fold(F::zero(), |a, &b| a.max(b));
+
+    if scale <= F::zero() {
+        return Ok(total_forces);
+    }
+
+    for (i, j) in iproduct!(0..n_agents, 0..n_agents).filter(|(i, j)| i != j) {
+        let distance = distances[[i, j]];
 
-        let force = gravity * mass_i * mass_j * difference / distance;
+        if distance <= scale * F::epsilon() {
+            continue;
+        }
+
+        let distance = distance.max(scale * F::cast(DISTANCE_FLOOR));
+
+        let difference = &agents[j].distribution - &agents[i].distribution;
+
+        let force = difference * (gravity * masses[i] * masses[j] / distance);
 
-        let random_factor = random::<f64>();
+        let random_factor = F::cast(rng.gen::<f64>());
 
         total_forces
             .slice_mut(s![i, .., ..])
             .scaled_add(random_factor, &force);
     }
 
-    total_forces
+    Ok(total_forces)
 }
 
 #[inline(always)]
@@ -120,7 +264,34 @@ fn gravity(initial: f64, decay: f64, time: f64, max_time: f64) -> f64 {
     initial * (-decay * time / max_time).exp()
 }
 
-pub fn fit(data: &Data, params: Parameters) -> Result<Fuzzy, Box<dyn Error>> {
+// Population state carried between iterations, exposed for the island model.
+// The velocity tensor is n_agents x n_samples x n_classes, so running in f32
+// halves the memory of large populations.
+#[derive(Debug, Clone)]
+pub struct Swarm<F = f64> {
+    pub agents: Vec<Fuzzy<F>>,
+    pub velocities: Array3<F>
+}
+
+pub fn init<F: Float, R: Rng>(data: &Data<F>, params: &Parameters, rng: &mut R) -> Result<Swarm<F>, ClusteringError> {
+    params.validate()?;
+
+    let n_samples = data.records.nrows();
+    let Parameters { n_classes, n_agents, metric, initialization, .. } = *params;
+
+    let agents = initialization::population(data, n_agents, n_classes, initialization, metric, rng)?;
+    let velocities = Array3::<F>::zeros((n_agents, n_samples, n_classes));
+
+    Ok(Swarm { agents, velocities })
+}
+
+pub fn step<F: Float, R: Rng>(
+    data: &Data<F>,
+    params: &Parameters,
+    swarm: &mut Swarm<F>,
+    time: usize,
+    rng: &mut R
+) -> Result<(), ClusteringError> {
     let n_samples = data.records.nrows();
 
     let Parameters {
@@ -130,66 +301,157 @@ pub fn fit(data: &Data, params: Parameters) -> Result<Fuzzy, Box<dyn Error>> {
         initial_gravity,
         gravity_decay,
         distance,
-        normalization
-    } = params;
+        metric,
+        normalization,
+        refinement,
+        mutation,
+        ..
+    } = *params;
+
+    let Swarm { agents, velocities } = swarm;
+
+    let gravity = F::cast(gravity(
+        initial_gravity,
+        gravity_decay,
+        time as f64,
+        max_iterations as f64
+    ));
 
-    let mut agents: Vec<Fuzzy> = (0..n_agents)
-        .map(|_| Fuzzy::random(n_samples, n_classes))
-        .collect();
+    let fitness = fitness(agents, data, metric);
 
-    let mut velocities: Array3<f64> =
-        Array3::<f64>::zeros((n_agents, n_samples, n_classes));
+    let masses = masses(&fitness)?;
 
-    let max_time = max_iterations as f64;
+    let best_index = fitness
+        .argmax()
+        .ok_or_else(|| ClusteringError::DegeneratePopulation("fitness of the population is empty or NaN".into()))?;
+    let best = agents[best_index].distribution.clone();
 
-    for time in 0..max_iterations {
-        let gravity = gravity(
-            initial_gravity,
-            gravity_decay,
-            time as f64,
-            max_time
-        );
+    let field = Field { gravity, masses: &masses, distance };
+    let mut forces = total_forces(n_samples, n_classes, agents, &field, rng)?;
 
-        let fitness = fitness(&agents, &data);
+    let masses = masses.into_shape((n_agents, 1, 1))?;
 
-        let masses = masses(&fitness);
+    forces /= &masses;
 
-        let mut forces = total_forces(
-            n_samples,
-            n_classes,
-            n_agents,
-            gravity,
-            &masses,
-            &agents,
-            distance
-        );
+    let randomizer = Array3::random_using((n_agents, 1, 1), Uniform::new(F::zero(), F::one()), rng);
+
+    *velocities *= &randomizer;
+    *velocities += &forces;
+
+    // println!("Min-Max velocity: {:?}", velocities.clone().into_iter().collect_vec().min_max());
+
+    for (i, agent) in agents.iter_mut().enumerate() {
+        agent.distribution += &velocities.slice(s![i, .., ..]);
+
+        if i != best_index {
+            mutation.apply(agent, &best, rng);
+        }
+
+        match normalization {
+            Normalization::ReLU => agent.distribution.relu_inplace(),
+            Normalization::MinMax => agent.distribution.minmax_inplace(),
+            Normalization::Logistic => agent.distribution.logistic_inplace()
+        };
+    }
+
+    if refinement.is_due(time) {
+        let mut fitness = self::fitness(agents, data, metric);
+        refinement.refine_best(data, agents, &mut fitness, &metric);
+    }
+
+    Ok(())
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+// Also returns the fitness of the swarm after every iteration
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    let mut history = History::new();
+    let best = run(data, params, Some(&mut history))?;
+
+    Ok((best, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, mut history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    let mut rng = seeded_rng(params.seed);
 
-        let masses = masses
-            .into_shape((n_agents, 1, 1))
-            .unwrap();
+    let mut swarm = init(data, &params, &mut rng)?;
 
-        forces /= &masses;
+    for time in 0..params.max_iterations {
+        step(data, &params, &mut swarm, time, &mut rng)?;
 
-        let randomizer = Array3::random((n_agents, 1, 1), Uniform::new(0.0, 1.0));
+        if let Some(history) = history.as_deref_mut() {
+            history.record(&fitness(&swarm.agents, data, params.metric));
+        }
+    }
+
+    let fitness = fitness(&swarm.agents, data, params.metric);
+    let best = fitness
+        .argmax()
+        .ok_or_else(|| ClusteringError::DegeneratePopulation("fitness of the population is empty or NaN".into()))?;
+
+    Ok(params.refinement.finalize(data, swarm.agents[best].clone(), &params.metric))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use linfa::ParamGuard;
 
-        velocities *= &randomizer;
-        velocities += &forces;
+    const ALL: [Distance; 5] = [
+        Distance::Cosine,
+        Distance::L1,
+        Distance::L2,
+        Distance::LInf,
+        Distance::Hamming
+    ];
 
-        // println!("Min-Max velocity: {:?}", velocities.clone().into_iter().collect_vec().min_max());
+    #[test]
+    fn forces_bounded_for_pseudometric_ties() {
+        let mut rng = seeded_rng(Some(7));
+        let base = Fuzzy::<f64>::random_using(20, 3, &mut rng);
 
-        for (i, agent) in agents.iter_mut().enumerate() {
-            agent.distribution += &velocities.slice(s![i, .., ..]);
+        // Proportional agents are at cosine distance zero, a small perturbation
+        // keeps every argmax and so the Hamming distance zero
+        let mut scaled = base.clone();
+        scaled.distribution *= 2.0;
 
-            match normalization {
-                Normalization::ReLU => agent.distribution.relu_inplace(),
-                Normalization::MinMax => agent.distribution.minmax_inplace(),
-                Normalization::Logistic => agent.distribution.logistic_inplace()
-            };
+        let mut perturbed = base.clone();
+        perturbed.distribution += 1e-9;
+
+        let agents = vec![base, scaled, perturbed, Fuzzy::random_using(20, 3, &mut rng)];
+        let masses = Array1::from_elem(agents.len(), 0.25);
+
+        for distance in ALL {
+            let field = Field { gravity: 1.0, masses: &masses, distance };
+            let forces = total_forces(20, 3, &agents, &field, &mut rng).unwrap();
+
+            assert!(forces.iter().all(|x| x.is_finite()));
+            assert!(forces.iter().all(|x| x.abs() < 1e6), "{:?} produced a force of {}", distance, forces.fold(0.0, |a: f64, &b| a.max(b.abs())));
         }
     }
 
-    let fitness = fitness(&agents, &data);
-    let best = fitness.argmax().unwrap();
+    #[test]
+    fn fit_stays_finite_with_every_distance() {
+        let data = linfa_datasets::iris();
+
+        for distance in ALL {
+            for seed in 0..3 {
+                let params = Parameters::builder(3)
+                    .n_agents(8)
+                    .max_iterations(30)
+                    .distance(distance)
+                    .seed(seed)
+                    .check()
+                    .unwrap();
 
-    Ok(agents[best].clone())
+                let solution = fit(&data, params).unwrap();
+
+                assert!(solution.distribution.iter().all(|x| x.is_finite()), "{:?} diverged with seed {}", distance, seed);
+            }
+        }
+    }
 }
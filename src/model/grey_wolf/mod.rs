@@ -0,0 +1,156 @@
+use std::cmp::Ordering;
+
+use linfa::{Float, ParamGuard};
+use ndarray::Array2;
+use ndarray_rand::{rand_distr::Uniform, RandomExt};
+
+use super::{distance::Measure, history::History, initialization::Initialization, memetic::Refinement, runner::{self, Settings}, solution::Fuzzy};
+use crate::{error::ClusteringError, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn builder(n_classes: usize) -> ParametersBuilder {
+        ParametersBuilder(Parameters {
+            n_classes,
+            n_agents: 20,
+            max_iterations: 500,
+            metric: Measure::Euclidean,
+            initialization: Initialization::Random,
+            refinement: Refinement::None,
+            seed: None
+        })
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            n_classes: self.n_classes,
+            n_agents: self.n_agents,
+            max_iterations: self.max_iterations,
+            metric: self.metric,
+            initialization: self.initialization,
+            refinement: self.refinement,
+            seed: self.seed
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        self.settings().validate(1)
+    }
+}
+
+// Unchecked parameters with defaults, turned into Parameters by ParamGuard::check
+#[derive(Debug, Clone, Copy)]
+pub struct ParametersBuilder(Parameters);
+
+impl ParametersBuilder {
+    pub fn n_agents(mut self, n_agents: usize) -> Self {
+        self.0.n_agents = n_agents;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.0.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn metric(mut self, metric: Measure) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    pub fn initialization(mut self, initialization: Initialization) -> Self {
+        self.0.initialization = initialization;
+        self
+    }
+
+    pub fn refinement(mut self, refinement: Refinement) -> Self {
+        self.0.refinement = refinement;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.0.seed = Some(seed);
+        self
+    }
+}
+
+impl ParamGuard for ParametersBuilder {
+    type Checked = Parameters;
+    type Error = ClusteringError;
+
+    fn check_ref(&self) -> Result<&Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(&self.0)
+    }
+
+    fn check(self) -> Result<Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+// Indices of the alpha, beta and delta wolves
+fn leaders<F: Float>(fitness: &[F]) -> [usize; 3] {
+    let mut order = (0..fitness.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| fitness[b].partial_cmp(&fitness[a]).unwrap_or(Ordering::Equal));
+
+    let last = order.len() - 1;
+    [order[0], order[1.min(last)], order[2.min(last)]]
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    runner::with_history(|history| run(data, params, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    params.validate()?;
+
+    let n_samples = data.records.nrows();
+    let Parameters { n_classes, max_iterations, metric, .. } = params;
+
+    runner::run(data, params.settings(), history, |pack, time, rng| {
+        let decay_factor = F::cast(2.0 - 2.0 * time as f64 / max_iterations as f64);
+
+        let leaders = leaders(&pack.fitness).map(|i| pack.agents[i].distribution.clone());
+
+        for (i, agent) in pack.agents.iter_mut().enumerate() {
+            let mut position = Array2::<F>::zeros((n_samples, n_classes));
+
+            for leader in leaders.iter() {
+                let decay = Array2::random_using(
+                    (n_samples, n_classes),
+                    Uniform::new(-F::one(), F::one()),
+                    rng
+                ) * decay_factor;
+
+                let randomizer = Array2::random_using(
+                    (n_samples, n_classes),
+                    Uniform::new(F::zero(), F::cast(2.0)),
+                    rng
+                );
+
+                let displacement = (&randomizer * leader - &agent.distribution).mapv_into(|x| x.abs());
+                position += &(leader - &decay * &displacement);
+            }
+
+            agent.distribution = position / F::cast(3.0);
+            pack.fitness[i] = agent.fitness_with(data, &metric);
+        }
+
+        Ok(())
+    })
+}
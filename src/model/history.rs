@@ -0,0 +1,41 @@
+use linfa::Float;
+
+// Best, mean and worst fitness of the population after every iteration,
+// recorded by the fit_with_history variants of the optimizers, along with the
+// best fitness found up to each iteration
+#[derive(Debug, Clone, Default)]
+pub struct History {
+    pub best: Vec<f64>,
+    pub mean: Vec<f64>,
+    pub worst: Vec<f64>,
+    pub best_so_far: Vec<f64>
+}
+
+impl History {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record<F: Float>(&mut self, fitness: &[F]) {
+        let fitness = fitness
+            .iter()
+            .map(|x| x.to_f64().unwrap_or(f64::NAN))
+            .collect::<Vec<_>>();
+
+        let best = fitness.iter().cloned().fold(f64::NEG_INFINITY, f64::max);
+        let best_so_far = self.best_so_far.last().map_or(best, |&previous| previous.max(best));
+
+        self.best.push(best);
+        self.best_so_far.push(best_so_far);
+        self.mean.push(fitness.iter().sum::<f64>() / fitness.len() as f64);
+        self.worst.push(fitness.iter().cloned().fold(f64::INFINITY, f64::min));
+    }
+
+    pub fn len(&self) -> usize {
+        self.best.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.best.is_empty()
+    }
+}
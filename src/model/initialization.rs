@@ -0,0 +1,244 @@
+use std::cmp::Ordering;
+
+use linfa::Float;
+use ndarray::Array2;
+use rand::{seq::{index, SliceRandom}, Rng};
+
+use super::{distance::Measure, kmeans, solution::{Activated, Fuzzy}};
+use crate::{error::ClusteringError, preprocessing::missing, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Initialization {
+    Random,
+    KMeansPlusPlus { n_iterations: usize },
+    Opposition,
+    LatinHypercube,
+    Sobol,
+    DataPoints
+}
+
+// Every class must be able to hold at least one sample
+pub fn check_classes<F: Float>(data: &Data<F>, n_classes: usize) -> Result<(), ClusteringError> {
+    if n_classes == 0 {
+        return Err(ClusteringError::InvalidParameters("number of classes must be positive".into()));
+    }
+
+    if n_classes > data.records.nrows() {
+        return Err(ClusteringError::InvalidParameters(format!(
+            "cannot split {} samples into {} classes",
+            data.records.nrows(),
+            n_classes
+        )));
+    }
+
+    Ok(())
+}
+
+pub fn population<F: Float, R: Rng>(
+    data: &Data<F>,
+    n_agents: usize,
+    n_classes: usize,
+    initialization: Initialization,
+    metric: Measure,
+    rng: &mut R
+) -> Result<Vec<Fuzzy<F>>, ClusteringError> {
+    missing::check_with(data, metric)?;
+
+    if n_agents == 0 {
+        return Err(ClusteringError::DegeneratePopulation("population needs at least one agent".into()));
+    }
+
+    check_classes(data, n_classes)?;
+
+    let n_samples = data.records.nrows();
+
+    let agents = match initialization {
+        Initialization::Random => (0..n_agents)
+            .map(|_| Fuzzy::random_using(n_samples, n_classes, rng))
+            .collect(),
+        Initialization::KMeansPlusPlus { n_iterations } => (0..n_agents)
+            .map(|_| {
+                let centroids = kmeans::plus_plus_with(data, n_classes, &metric, rng);
+                // Metrics without a cluster center keep the seeds
+                let centroids = kmeans::lloyd_with(data, &centroids, n_iterations, F::zero(), &metric).unwrap_or(centroids);
+                Fuzzy::from_centroids_with(data, &centroids, &metric)
+            })
+            .collect(),
+        Initialization::Opposition => opposition(data, n_agents, n_classes, metric, rng),
+        Initialization::LatinHypercube => {
+            let points = latin_hypercube(n_agents, n_classes * data.records.ncols(), rng);
+            from_unit_points(data, n_classes, &points, metric)?
+        },
+        Initialization::Sobol => {
+            // One point per centroid, consecutive points forming the centroids of one agent
+            let n_features = data.records.ncols();
+            let points = sobol(n_agents * n_classes, n_features, rng)?
+                .into_shape((n_agents, n_classes * n_features))?;
+            from_unit_points(data, n_classes, &points, metric)?
+        },
+        Initialization::DataPoints => data_points(data, n_agents, n_classes, metric, rng)?
+    };
+
+    Ok(agents)
+}
+
+// Opposition-based learning (Rahnamayan et al., 2008): keep the fittest of
+// the random agents and their opposites
+fn opposition<F: Float, R: Rng>(data: &Data<F>, n_agents: usize, n_classes: usize, metric: Measure, rng: &mut R) -> Vec<Fuzzy<F>> {
+    let n_samples = data.records.nrows();
+
+    let mut candidates = (0..n_agents)
+        .flat_map(|_| {
+            let agent = Fuzzy::random_using(n_samples, n_classes, rng);
+            let mut opposite = agent.clone();
+            opposite.distribution.mapv_inplace(|x| F::one() - x);
+            [agent, opposite]
+        })
+        .map(|agent| (agent.fitness_with(data, &metric), agent))
+        .collect::<Vec<_>>();
+
+    candidates.sort_by(|(a, _), (b, _)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+
+    candidates
+        .into_iter()
+        .take(n_agents)
+        .map(|(_, agent)| agent)
+        .collect()
+}
+
+// Centroids drawn without replacement among the records
+fn data_points<F: Float, R: Rng>(data: &Data<F>, n_agents: usize, n_classes: usize, metric: Measure, rng: &mut R) -> Result<Vec<Fuzzy<F>>, ClusteringError> {
+    check_classes(data, n_classes)?;
+
+    let n_samples = data.records.nrows();
+
+    let agents = (0..n_agents)
+        .map(|_| {
+            let mut centroids = Array2::<F>::zeros((n_classes, data.records.ncols()));
+
+            for (k, i) in index::sample(rng, n_samples, n_classes).into_iter().enumerate() {
+                centroids.row_mut(k).assign(&data.records.row(i));
+            }
+
+            Fuzzy::from_centroids_with(data, &centroids, &metric)
+        })
+        .collect();
+
+    Ok(agents)
+}
+
+// Rows of the unit hypercube sample become the flattened centroids of one agent
+fn from_unit_points<F: Float>(data: &Data<F>, n_classes: usize, points: &Array2<f64>, metric: Measure) -> Result<Vec<Fuzzy<F>>, ClusteringError> {
+    let (lower, upper) = Activated::bounds(data);
+    let span = &upper - &lower;
+    let n_features = data.records.ncols();
+
+    points
+        .rows()
+        .into_iter()
+        .map(|point| {
+            let unit = point
+                .mapv(F::cast)
+                .into_shape((n_classes, n_features))?;
+
+            Ok(Fuzzy::from_centroids_with(data, &(unit * &span + &lower), &metric))
+        })
+        .collect()
+}
+
+pub fn latin_hypercube<R: Rng>(n_points: usize, n_dimensions: usize, rng: &mut R) -> Array2<f64> {
+    let mut points = Array2::<f64>::zeros((n_points, n_dimensions));
+
+    for mut column in points.columns_mut() {
+        let mut strata = (0..n_points).collect::<Vec<_>>();
+        strata.shuffle(rng);
+
+        for (x, stratum) in column.iter_mut().zip(strata) {
+            *x = (stratum as f64 + rng.gen::<f64>()) / n_points as f64;
+        }
+    }
+
+    points
+}
+
+// Primitive polynomial degree, coefficients and initial direction numbers
+// for dimensions 2.. of the Joe & Kuo (2008) table
+const SOBOL_DIRECTIONS: [(u32, u32, &[u32]); 20] = [
+    (1, 0, &[1]),
+    (2, 1, &[1, 3]),
+    (3, 1, &[1, 3, 1]),
+    (3, 2, &[1, 1, 1]),
+    (4, 1, &[1, 1, 3, 3]),
+    (4, 4, &[1, 3, 5, 13]),
+    (5, 2, &[1, 1, 5, 5, 17]),
+    (5, 4, &[1, 1, 5, 5, 5]),
+    (5, 7, &[1, 1, 7, 11, 19]),
+    (5, 11, &[1, 1, 5, 1, 1]),
+    (5, 13, &[1, 1, 1, 3, 11]),
+    (5, 14, &[1, 3, 5, 5, 31]),
+    (6, 1, &[1, 3, 3, 9, 7, 49]),
+    (6, 13, &[1, 1, 1, 15, 21, 21]),
+    (6, 16, &[1, 3, 1, 13, 27, 49]),
+    (6, 19, &[1, 1, 1, 15, 7, 5]),
+    (6, 22, &[1, 3, 1, 15, 13, 25]),
+    (6, 25, &[1, 1, 5, 5, 19, 61]),
+    (7, 1, &[1, 3, 7, 11, 23, 15, 103]),
+    (7, 4, &[1, 3, 7, 13, 13, 15, 69]),
+];
+
+const SOBOL_BITS: usize = 32;
+
+fn sobol_directions(dimension: usize) -> [u32; SOBOL_BITS] {
+    let mut directions = [0u32; SOBOL_BITS];
+
+    if dimension == 0 {
+        for (i, v) in directions.iter_mut().enumerate() {
+            *v = 1 << (SOBOL_BITS - 1 - i);
+        }
+        return directions;
+    }
+
+    let (degree, coefficients, initial) = SOBOL_DIRECTIONS[dimension - 1];
+    let degree = degree as usize;
+
+    for i in 0..SOBOL_BITS {
+        directions[i] = match i < degree {
+            true => initial[i] << (SOBOL_BITS - 1 - i),
+            false => {
+                let mut v = directions[i - degree] ^ (directions[i - degree] >> degree);
+                for k in 1..degree {
+                    v ^= ((coefficients >> (degree - 1 - k)) & 1) * directions[i - k];
+                }
+                v
+            }
+        };
+    }
+
+    directions
+}
+
+// Sobol sequence with a random digital shift, skipping the origin
+pub fn sobol<R: Rng>(n_points: usize, n_dimensions: usize, rng: &mut R) -> Result<Array2<f64>, ClusteringError> {
+    if n_dimensions > SOBOL_DIRECTIONS.len() + 1 {
+        return Err(ClusteringError::InvalidParameters(format!(
+            "Sobol initialization supports at most {} dimensions, got {}",
+            SOBOL_DIRECTIONS.len() + 1,
+            n_dimensions
+        )));
+    }
+
+    let mut points = Array2::<f64>::zeros((n_points, n_dimensions));
+
+    for (dimension, mut column) in points.columns_mut().into_iter().enumerate() {
+        let directions = sobol_directions(dimension);
+        let shift = rng.gen::<u32>();
+        let mut x = 0u32;
+
+        for (i, value) in column.iter_mut().enumerate() {
+            x ^= directions[i.trailing_ones() as usize];
+            *value = (x ^ shift) as f64 / (1u64 << SOBOL_BITS) as f64;
+        }
+    }
+
+    Ok(points)
+}
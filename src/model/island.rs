@@ -0,0 +1,247 @@
+use std::cmp::Ordering;
+
+use linfa::Float;
+use ndarray::s;
+use rand::Rng;
+
+use super::{distance::Measure, gravity, history::History, memetic::Refinement, solution::Fuzzy, whales};
+use crate::{error::ClusteringError, utility::sampling::seeded_rng, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Algorithm {
+    Gravity(gravity::Parameters),
+    Whales(whales::Parameters)
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum Topology {
+    // Island i sends its emigrants to island i + 1
+    Ring,
+    // Every island sends its emigrants to all the others
+    FullyConnected
+}
+
+// Island model: the islands evolve independently and every migration_interval
+// iterations the n_migrants fittest agents of each island replace the worst
+// agents of its neighbours. The per-island max_iterations and seed are
+// overridden by the ones given here.
+#[derive(Debug, Clone)]
+pub struct Parameters {
+    pub islands: Vec<Algorithm>,
+    pub max_iterations: usize,
+    pub migration_interval: usize,
+    pub n_migrants: usize,
+    pub topology: Topology,
+    pub seed: Option<u64>
+}
+
+enum Population<F> {
+    Gravity(gravity::Parameters, gravity::Swarm<F>),
+    Whales(whales::Parameters, Vec<Fuzzy<F>>)
+}
+
+impl<F: Float> Population<F> {
+    fn new<R: Rng>(data: &Data<F>, algorithm: Algorithm, max_iterations: usize, rng: &mut R) -> Result<Self, ClusteringError> {
+        let population = match algorithm {
+            Algorithm::Gravity(params) => {
+                let params = gravity::Parameters { max_iterations, ..params };
+                Population::Gravity(params, gravity::init(data, &params, rng)?)
+            },
+            Algorithm::Whales(params) => {
+                let params = whales::Parameters { max_iterations, ..params };
+                Population::Whales(params, whales::init(data, &params, rng)?)
+            }
+        };
+
+        Ok(population)
+    }
+
+    fn step<R: Rng>(&mut self, data: &Data<F>, time: usize, rng: &mut R) -> Result<(), ClusteringError> {
+        match self {
+            Population::Gravity(params, swarm) => gravity::step(data, params, swarm, time, rng),
+            Population::Whales(params, agents) => {
+                whales::step(data, params, agents, time, rng);
+                Ok(())
+            }
+        }
+    }
+
+    fn n_classes(&self) -> usize {
+        match self {
+            Population::Gravity(params, _) => params.n_classes,
+            Population::Whales(params, _) => params.n_classes
+        }
+    }
+
+    fn refinement(&self) -> Refinement {
+        match self {
+            Population::Gravity(params, _) => params.refinement,
+            Population::Whales(params, _) => params.refinement
+        }
+    }
+
+    fn metric(&self) -> Measure {
+        match self {
+            Population::Gravity(params, _) => params.metric,
+            Population::Whales(params, _) => params.metric
+        }
+    }
+
+    fn agents(&self) -> &Vec<Fuzzy<F>> {
+        match self {
+            Population::Gravity(_, swarm) => &swarm.agents,
+            Population::Whales(_, agents) => agents
+        }
+    }
+
+    // An immigrant starts at rest instead of inheriting the replaced agent's momentum
+    fn replace(&mut self, i: usize, immigrant: Fuzzy<F>) {
+        match self {
+            Population::Gravity(_, swarm) => {
+                swarm.agents[i] = immigrant;
+                swarm.velocities.slice_mut(s![i, .., ..]).fill(F::zero());
+            },
+            Population::Whales(_, agents) => agents[i] = immigrant
+        }
+    }
+
+    fn fitness(&self, data: &Data<F>) -> Vec<F> {
+        let metric = self.metric();
+
+        self.agents()
+            .iter()
+            .map(|agent| agent.fitness_with(data, &metric))
+            .collect()
+    }
+
+    // Agent indices from the fittest to the worst
+    fn ranking(&self, data: &Data<F>) -> Vec<(usize, F)> {
+        let mut ranking = self
+            .fitness(data)
+            .into_iter()
+            .enumerate()
+            .collect::<Vec<_>>();
+
+        ranking.sort_by(|(_, a), (_, b)| b.partial_cmp(a).unwrap_or(Ordering::Equal));
+        ranking
+    }
+}
+
+fn migrate<F: Float>(data: &Data<F>, populations: &mut [Population<F>], n_migrants: usize, topology: Topology) {
+    let n_islands = populations.len();
+
+    let emigrants = populations
+        .iter()
+        .map(|population| population
+            .ranking(data)
+            .into_iter()
+            .take(n_migrants)
+            .map(|(i, _)| population.agents()[i].clone())
+            .collect::<Vec<_>>()
+        )
+        .collect::<Vec<_>>();
+
+    for (target, population) in populations.iter_mut().enumerate() {
+        let sources = match topology {
+            Topology::Ring => vec![(target + n_islands - 1) % n_islands],
+            Topology::FullyConnected => (0..n_islands).filter(|&source| source != target).collect()
+        };
+
+        let immigrants = sources
+            .into_iter()
+            .flat_map(|source| emigrants[source].iter().cloned())
+            .collect::<Vec<_>>();
+
+        // The island's own best agent is never replaced
+        let worst = population
+            .ranking(data)
+            .into_iter()
+            .skip(1)
+            .rev()
+            .map(|(i, _)| i)
+            .collect::<Vec<_>>();
+
+        for (i, immigrant) in worst.into_iter().zip(immigrants) {
+            population.replace(i, immigrant);
+        }
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+// Also returns the fitness of all islands together after every iteration
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    let mut history = History::new();
+    let best = run(data, params, Some(&mut history))?;
+
+    Ok((best, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, mut history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    let Parameters {
+        islands,
+        max_iterations,
+        migration_interval,
+        n_migrants,
+        topology,
+        seed
+    } = params;
+
+    if islands.is_empty() {
+        return Err(ClusteringError::InvalidParameters("island model needs at least one island".into()));
+    }
+
+    let mut rng = seeded_rng(seed);
+
+    let mut populations = islands
+        .into_iter()
+        .map(|algorithm| Population::new(data, algorithm, max_iterations, &mut rng))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let n_classes = populations[0].n_classes();
+
+    if populations.iter().any(|population| population.n_classes() != n_classes) {
+        return Err(ClusteringError::InvalidParameters("all islands must use the same number of classes".into()));
+    }
+
+    // Fitness under different metrics is not comparable across islands
+    if populations.iter().any(|population| population.metric() != populations[0].metric()) {
+        return Err(ClusteringError::InvalidParameters("all islands must use the same metric".into()));
+    }
+
+    for time in 0..max_iterations {
+        for population in populations.iter_mut() {
+            population.step(data, time, &mut rng)?;
+        }
+
+        // An interval of zero never migrates
+        if (time + 1).is_multiple_of(migration_interval) {
+            migrate(data, &mut populations, n_migrants, topology);
+        }
+
+        if let Some(history) = history.as_deref_mut() {
+            let fitness = populations
+                .iter()
+                .flat_map(|population| population.fitness(data))
+                .collect::<Vec<_>>();
+
+            history.record(&fitness);
+        }
+    }
+
+    let (island, best, _) = populations
+        .iter()
+        .enumerate()
+        .map(|(island, population)| {
+            let (best, fitness) = population.ranking(data)[0];
+            (island, best, fitness)
+        })
+        .max_by(|(_, _, a), (_, _, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .ok_or_else(|| ClusteringError::DegeneratePopulation("no island has any agents".into()))?;
+
+    let population = &populations[island];
+
+    Ok(population.refinement().finalize(data, population.agents()[best].clone(), &population.metric()))
+}
@@ -1,31 +1,175 @@
-use std::error::Error;
-
 use linfa::{
     self,
     prelude::{Fit, Predict},
     Dataset,
+    Float,
 };
 use linfa_clustering::{self, KMeans};
 use linfa_nn::distance::L2Dist;
-use rand::thread_rng;
+use ndarray::{Array1, Array2, Axis};
+use ndarray_stats::QuantileExt;
+use rand::{distributions::{Distribution, WeightedIndex}, seq::SliceRandom, thread_rng, Rng};
+
+use crate::{error::ClusteringError, preprocessing::missing, utility::sampling::seeded_rng, Data};
 
-use crate::Data;
+use super::{distance::{self, Euclidean, Measure, Metric, SquaredEuclidean}, solution::Discrete};
 
-use super::solution::Discrete;
+pub fn fit<F: Float>(
+    data: &Data<F>,
+    n_clusters: usize,
+    max_iterations: usize,
+    tolerance: f64
+) -> Result<Discrete, ClusteringError> {
+    fit_with(data, n_clusters, max_iterations, tolerance, Measure::Euclidean)
+}
 
-pub fn fit(
-    data: &Data,
+// Euclidean distances go through linfa, every other metric through fit_seeded
+pub fn fit_with<F: Float>(
+    data: &Data<F>,
     n_clusters: usize,
     max_iterations: usize,
     tolerance: f64,
-) -> Result<Discrete, Box<dyn Error>> {
+    metric: Measure
+) -> Result<Discrete, ClusteringError> {
+    missing::check_with(data, metric)?;
+    metric.validate()?;
+
+    if metric != Measure::Euclidean {
+        return fit_seeded(data, n_clusters, max_iterations, F::cast(tolerance), &metric, None);
+    }
+
     let dataset = Dataset::from(data.records.to_owned());
     let rng = thread_rng();
 
     let model = KMeans::params_with(n_clusters, rng, L2Dist)
         .max_n_iterations(max_iterations as u64)
-        .tolerance(tolerance)
-        .fit(&dataset)?;
+        .tolerance(F::cast(tolerance))
+        .fit(&dataset)
+        .map_err(|error| ClusteringError::InvalidParameters(error.to_string()))?;
 
     Ok(Discrete::from_prediction(model.predict(&dataset), n_clusters))
 }
+
+// k-means++ seeding (Arthur & Vassilvitskii, 2007)
+pub fn plus_plus<F: Float, R: Rng>(data: &Data<F>, n_clusters: usize, rng: &mut R) -> Array2<F> {
+    plus_plus_with(data, n_clusters, &Euclidean, rng)
+}
+
+// Seeding with probabilities proportional to the squared distance under the metric
+pub fn plus_plus_with<F: Float, M: Metric<F> + ?Sized, R: Rng>(data: &Data<F>, n_clusters: usize, metric: &M, rng: &mut R) -> Array2<F> {
+    let samples = data.records();
+    let n_samples = samples.nrows();
+
+    let mut centroids = Array2::<F>::zeros((n_clusters, samples.ncols()));
+    centroids.row_mut(0).assign(&samples.row(rng.gen_range(0..n_samples)));
+
+    let mut nearest = Array1::from_elem(n_samples, F::infinity());
+
+    // Draws are weighted by squared distances, which squared metrics already give
+    let squared = metric.is_squared();
+
+    for k in 1..n_clusters {
+        for (i, record) in samples.axis_iter(Axis(0)).enumerate() {
+            let distance = metric.distance(record, centroids.row(k - 1));
+            let weight = match squared {
+                true => distance,
+                false => distance * distance
+            };
+            nearest[i] = nearest[i].min(weight);
+        }
+
+        // Records infinitely far from every centroid, e.g. sharing no observed
+        // feature with them, are the farthest and are drawn first
+        let unreached = (0..n_samples).filter(|&i| !nearest[i].is_finite()).collect::<Vec<_>>();
+
+        let next = match unreached.choose(rng) {
+            Some(&i) => i,
+            None => match WeightedIndex::new(nearest.iter()) {
+                Ok(weights) => weights.sample(rng),
+                Err(_) => rng.gen_range(0..n_samples)
+            }
+        };
+
+        centroids.row_mut(k).assign(&samples.row(next));
+    }
+
+    centroids
+}
+
+pub fn assign<F: Float>(data: &Data<F>, centroids: &Array2<F>) -> Discrete {
+    assign_with(data, centroids, &SquaredEuclidean)
+}
+
+pub fn assign_with<F: Float, M: Metric<F> + ?Sized>(data: &Data<F>, centroids: &Array2<F>, metric: &M) -> Discrete {
+    let indicators = distance::to_centroids(data, centroids, metric).map_axis(
+        Axis(1),
+        |row| row.argmin().unwrap_or(0)
+    );
+
+    Discrete::from_prediction(indicators, centroids.nrows())
+}
+
+// Lloyd iterations starting from the given centroids, empty clusters keep their previous centroid
+pub fn lloyd<F: Float>(data: &Data<F>, centroids: &Array2<F>, n_iterations: usize) -> Array2<F> {
+    // The squared Euclidean center is the mean, which every non-empty cluster has
+    lloyd_with(data, centroids, n_iterations, F::zero(), &SquaredEuclidean).unwrap_or_else(|_| centroids.to_owned())
+}
+
+// Assignment under an arbitrary metric with updates to the metric's center,
+// e.g. the median for Manhattan; stops early once no centroid moves by more
+// than the tolerance. Fails for metrics without a center.
+pub fn lloyd_with<F: Float, M: Metric<F> + ?Sized>(
+    data: &Data<F>,
+    centroids: &Array2<F>,
+    n_iterations: usize,
+    tolerance: F,
+    metric: &M
+) -> Result<Array2<F>, ClusteringError> {
+    let samples = data.records();
+    let mut centroids = centroids.to_owned();
+
+    for _ in 0..n_iterations {
+        let assignment = assign_with(data, &centroids, metric);
+        let mut shift = F::zero();
+
+        for (k, members) in assignment.members().into_iter().enumerate().filter(|(_, members)| !members.is_empty()) {
+            let updated = metric
+                .center(samples.select(Axis(0), &members).view())
+                .ok_or_else(|| ClusteringError::InvalidParameters("metric has no cluster center for Lloyd updates".into()))?;
+
+            shift = shift.max((&centroids.row(k) - &updated).mapv(|x| x.abs()).sum());
+            centroids.row_mut(k).assign(&updated);
+        }
+
+        if shift <= tolerance {
+            break;
+        }
+    }
+
+    Ok(centroids)
+}
+
+// k-means++ seeding followed by Lloyd iterations under the given metric
+pub fn fit_seeded<F: Float, M: Metric<F> + ?Sized>(
+    data: &Data<F>,
+    n_clusters: usize,
+    max_iterations: usize,
+    tolerance: F,
+    metric: &M,
+    seed: Option<u64>
+) -> Result<Discrete, ClusteringError> {
+    if n_clusters == 0 || n_clusters > data.records.nrows() {
+        return Err(ClusteringError::InvalidParameters(format!(
+            "number of clusters must lie in 1..={}, got {}",
+            data.records.nrows(),
+            n_clusters
+        )));
+    }
+
+    let mut rng = seeded_rng(seed);
+
+    let centroids = plus_plus_with(data, n_clusters, metric, &mut rng);
+    let centroids = lloyd_with(data, &centroids, max_iterations, tolerance, metric)?;
+
+    Ok(assign_with(data, &centroids, metric))
+}
@@ -0,0 +1,74 @@
+use std::cmp::Ordering;
+
+use linfa::Float;
+
+use super::{distance::{Measure, Metric}, kmeans, solution::Fuzzy};
+use crate::{error::ClusteringError, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Refinement {
+    None,
+    Final { n_iterations: usize },
+    // With finalize, the returned best agent is also refined after the last iteration
+    Periodic { period: usize, n_best: usize, n_iterations: usize, finalize: bool }
+}
+
+impl Refinement {
+    // Lloyd iterations need a cluster center under the metric
+    pub fn validate(&self, metric: Measure) -> Result<(), ClusteringError> {
+        if let Refinement::Periodic { period, n_best, .. } = *self {
+            if period == 0 {
+                return Err(ClusteringError::InvalidParameters("refinement period must be positive".into()));
+            }
+
+            if n_best == 0 {
+                return Err(ClusteringError::InvalidParameters("periodic refinement needs at least one agent to refine".into()));
+            }
+        }
+
+        match self {
+            Refinement::None => Ok(()),
+            _ if metric.has_center() => Ok(()),
+            _ => Err(ClusteringError::InvalidParameters(format!("memetic refinement has no cluster center under {:?}", metric)))
+        }
+    }
+
+    pub fn is_due(&self, time: usize) -> bool {
+        match *self {
+            Refinement::Periodic { period, .. } => (time + 1).is_multiple_of(period),
+            _ => false
+        }
+    }
+
+    // Refines the n_best fittest agents in place and updates their fitness
+    pub fn refine_best<F: Float, M: Metric<F> + ?Sized>(&self, data: &Data<F>, agents: &mut [Fuzzy<F>], fitness: &mut [F], metric: &M) {
+        let Refinement::Periodic { n_best, n_iterations, .. } = *self else {
+            return;
+        };
+
+        let mut order = (0..agents.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| fitness[b].partial_cmp(&fitness[a]).unwrap_or(Ordering::Equal));
+
+        for &i in order.iter().take(n_best) {
+            agents[i] = refine(data, &agents[i], n_iterations, metric);
+            fitness[i] = agents[i].fitness_with(data, metric);
+        }
+    }
+
+    pub fn finalize<F: Float, M: Metric<F> + ?Sized>(&self, data: &Data<F>, best: Fuzzy<F>, metric: &M) -> Fuzzy<F> {
+        match *self {
+            Refinement::Final { n_iterations }
+            | Refinement::Periodic { n_iterations, finalize: true, .. } => refine(data, &best, n_iterations, metric),
+            _ => best
+        }
+    }
+}
+
+// Lloyd iterations from the agent's clusters under the metric; metrics
+// without a cluster center leave the agent as it is
+pub fn refine<F: Float, M: Metric<F> + ?Sized>(data: &Data<F>, agent: &Fuzzy<F>, n_iterations: usize, metric: &M) -> Fuzzy<F> {
+    match kmeans::lloyd_with(data, &agent.centroids_with(data, metric), n_iterations, F::zero(), metric) {
+        Ok(centroids) => Fuzzy::from_centroids_with(data, &centroids, metric),
+        Err(_) => agent.clone()
+    }
+}
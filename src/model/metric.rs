@@ -1,10 +1,16 @@
-use super::solution::Discrete;
+use std::f64::consts::PI;
 
-pub fn accuracy(truth: &Discrete, prediction: &Discrete) -> Result<f64, ndarray::ErrorKind> {
+use linfa::{dataset::Records, Float};
+use ndarray::Axis;
+
+use super::{distance::{Euclidean, Metric}, solution::Discrete};
+use crate::{error::ClusteringError, Data};
+
+pub fn accuracy(truth: &Discrete, prediction: &Discrete) -> Result<f64, ClusteringError> {
     let n_samples = truth.n_samples;
 
     if n_samples != prediction.indicators.dim() {
-        return Err(ndarray::ErrorKind::IncompatibleShape);
+        return Err(ClusteringError::ShapeMismatch("labels and records differ in length".into()));
     }
 
     let mut matching: usize = 0;
@@ -25,3 +31,148 @@ pub fn accuracy(truth: &Discrete, prediction: &Discrete) -> Result<f64, ndarray:
 
     Ok(matching as f64 / n_samples as f64)
 }
+
+pub fn sse<F: Float>(data: &Data<F>, prediction: &Discrete) -> Result<F, ClusteringError> {
+    let samples = data.records();
+
+    if samples.nrows() != prediction.indicators.dim() {
+        return Err(ClusteringError::ShapeMismatch("labels and records differ in length".into()));
+    }
+
+    let centroids = prediction.centroids(data);
+
+    let total = samples
+        .axis_iter(Axis(0))
+        .zip(prediction.indicators.iter())
+        .map(|(record, &cluster)| (&record - &centroids.row(cluster))
+            .mapv_into(|x| x * x)
+            .sum()
+        )
+        .sum();
+
+    Ok(total)
+}
+
+pub fn silhouette<F: Float>(data: &Data<F>, prediction: &Discrete) -> Result<F, ClusteringError> {
+    silhouette_with(data, prediction, &Euclidean)
+}
+
+pub fn silhouette_with<F: Float, M: Metric<F> + ?Sized>(data: &Data<F>, prediction: &Discrete, metric: &M) -> Result<F, ClusteringError> {
+    let samples = data.records();
+    let n_samples = samples.nrows();
+
+    if n_samples != prediction.indicators.dim() {
+        return Err(ClusteringError::ShapeMismatch("labels and records differ in length".into()));
+    }
+
+    let sizes = prediction.cluster_sizes();
+    let labels = &prediction.indicators;
+
+    let total: F = (0..n_samples)
+        .map(|i| {
+            let own = labels[i];
+
+            if sizes[own] <= 1 {
+                return F::zero();
+            }
+
+            let mut sums = vec![F::zero(); prediction.n_classes];
+
+            for j in 0..n_samples {
+                sums[labels[j]] += metric.distance(samples.row(i), samples.row(j));
+            }
+
+            let a = sums[own] / F::cast(sizes[own] - 1);
+            let b = sums
+                .iter()
+                .zip(sizes.iter())
+                .enumerate()
+                .filter(|&(cluster, (_, &size))| cluster != own && size > 0)
+                .map(|(_, (&sum, &size))| sum / F::cast(size))
+                .fold(F::infinity(), F::min);
+
+            match b.is_finite() {
+                true => (b - a) / a.max(b),
+                false => F::zero()
+            }
+        })
+        .sum();
+
+    Ok(total / F::cast(n_samples))
+}
+
+// Spherical Gaussian BIC as used by X-means (Pelleg & Moore, 2000), higher is better
+pub fn bic<F: Float>(data: &Data<F>, prediction: &Discrete) -> Result<F, ClusteringError> {
+    let n_samples = F::cast(data.nsamples());
+    let n_features = F::cast(data.nfeatures());
+    let n_classes = F::cast(prediction.n_classes);
+    let half = F::cast(0.5);
+
+    let variance = sse(data, prediction)? / (n_features * (n_samples - n_classes)).max(F::one());
+
+    let log_likelihood: F = prediction
+        .cluster_sizes()
+        .into_iter()
+        .filter(|&size| size > 0)
+        .map(|size| {
+            let size = F::cast(size);
+            size * size.ln()
+                - size * n_samples.ln()
+                - size * n_features * half * (F::cast(2.0 * PI) * variance).ln()
+                - n_features * (size - F::one()) * half
+        })
+        .sum();
+
+    let n_parameters = (n_classes - F::one()) + n_classes * n_features + F::one();
+
+    Ok(log_likelihood - n_parameters * half * n_samples.ln())
+}
+
+// Davies-Bouldin index over non-empty clusters, lower is better
+pub fn davies_bouldin<F: Float>(data: &Data<F>, prediction: &Discrete) -> Result<F, ClusteringError> {
+    davies_bouldin_with(data, prediction, &Euclidean)
+}
+
+pub fn davies_bouldin_with<F: Float, M: Metric<F> + ?Sized>(data: &Data<F>, prediction: &Discrete, metric: &M) -> Result<F, ClusteringError> {
+    let samples = data.records();
+
+    if samples.nrows() != prediction.indicators.dim() {
+        return Err(ClusteringError::ShapeMismatch("labels and records differ in length".into()));
+    }
+
+    let centroids = prediction.centroids_with(data, metric);
+    let sizes = prediction.cluster_sizes();
+
+    let mut scatter = vec![F::zero(); prediction.n_classes];
+
+    for (record, &cluster) in samples.axis_iter(Axis(0)).zip(prediction.indicators.iter()) {
+        scatter[cluster] += metric.distance(record, centroids.row(cluster));
+    }
+
+    let clusters = (0..prediction.n_classes)
+        .filter(|&c| sizes[c] > 0)
+        .collect::<Vec<_>>();
+
+    if clusters.len() < 2 {
+        return Ok(F::infinity());
+    }
+
+    clusters
+        .iter()
+        .for_each(|&c| scatter[c] /= F::cast(sizes[c]));
+
+    let total: F = clusters
+        .iter()
+        .map(|&i| clusters
+            .iter()
+            .filter(|&&j| j != i)
+            .map(|&j| {
+                let separation = metric.distance(centroids.row(i), centroids.row(j));
+                (scatter[i] + scatter[j]) / separation
+            })
+            .fold(F::neg_infinity(), F::max)
+        )
+        .sum();
+
+    Ok(total / F::cast(clusters.len()))
+}
@@ -1,5 +1,29 @@
 pub mod solution;
 pub mod metric;
+pub mod distance;
+pub mod selection;
 pub mod kmeans;
+pub mod fcm;
+pub mod dbscan;
+pub mod agglomerative;
+pub mod gmm;
+pub mod initialization;
+pub mod memetic;
+pub mod history;
+pub mod mutation;
+pub mod partition;
+pub mod runner;
 pub mod gravity;
-pub mod whales;
\ No newline at end of file
+pub mod whales;
+pub mod grey_wolf;
+pub mod sine_cosine;
+pub mod bee_colony;
+pub mod firefly;
+pub mod annealing;
+pub mod tabu;
+pub mod ant_colony;
+pub mod cuckoo;
+pub mod bat;
+pub mod tlbo;
+pub mod multiobjective;
+pub mod island;
\ No newline at end of file
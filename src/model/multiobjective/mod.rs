@@ -0,0 +1,376 @@
+use std::cmp::Ordering;
+
+use linfa::Float;
+use ndarray::{Array1, Axis};
+use rand::{seq::SliceRandom, Rng};
+
+use super::{initialization, kmeans, metric, solution::Discrete};
+use crate::{error::ClusteringError, preprocessing::missing, utility::sampling::seeded_rng, Data};
+
+// All objectives are minimized
+#[derive(Debug, Clone, Copy)]
+pub enum Objective {
+    Compactness,
+    Sse,
+    // Over the n_neighbors nearest neighbours given in the parameters
+    Connectivity,
+    NClusters
+}
+
+impl Objective {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Objective::Compactness => "Compactness",
+            Objective::Sse => "SSE",
+            Objective::Connectivity => "Connectivity",
+            Objective::NClusters => "Number of clusters"
+        }
+    }
+}
+
+// NSGA-II (Deb et al., 2002) over label vectors with at most n_classes clusters
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub crossover_rate: f64,
+    pub mutation_rate: f64,
+    pub n_neighbors: usize,
+    pub seed: Option<u64>
+}
+
+#[derive(Debug, Clone)]
+pub struct Front {
+    pub objectives: Vec<Objective>,
+    pub labelings: Vec<Discrete>,
+    pub scores: Vec<Vec<f64>>
+}
+
+impl Front {
+    // Knee point: the largest normalized distance below the line joining the
+    // extremes of a bi-objective front, or the point closest to the ideal one
+    pub fn knee(&self) -> Option<usize> {
+        let n_objectives = self.objectives.len();
+
+        let lower = (0..n_objectives)
+            .map(|m| self.scores.iter().map(|s| s[m]).fold(f64::INFINITY, f64::min))
+            .collect::<Vec<_>>();
+        let upper = (0..n_objectives)
+            .map(|m| self.scores.iter().map(|s| s[m]).fold(f64::NEG_INFINITY, f64::max))
+            .collect::<Vec<_>>();
+
+        let normalized = self.scores
+            .iter()
+            .map(|s| (0..n_objectives)
+                .map(|m| match upper[m] - lower[m] {
+                    range if range > 0.0 => (s[m] - lower[m]) / range,
+                    _ => 0.0
+                })
+                .collect::<Vec<_>>()
+            )
+            .collect::<Vec<_>>();
+
+        let key = |point: &Vec<f64>| match n_objectives {
+            2 => point[0] + point[1] - 1.0,
+            _ => point.iter().map(|x| x * x).sum::<f64>().sqrt()
+        };
+
+        (0..normalized.len()).min_by(|&a, &b| key(&normalized[a]).total_cmp(&key(&normalized[b])))
+    }
+}
+
+fn neighbors<F: Float>(data: &Data<F>, n_neighbors: usize) -> Vec<Vec<usize>> {
+    let records = &data.records;
+
+    records
+        .axis_iter(Axis(0))
+        .map(|a| {
+            let mut distances = records
+                .axis_iter(Axis(0))
+                .map(|b| (&a - &b).mapv_into(|x| x * x).sum())
+                .enumerate()
+                .collect::<Vec<_>>();
+
+            distances.sort_by(|(_, x), (_, y)| x.partial_cmp(y).unwrap_or(Ordering::Equal));
+
+            distances
+                .into_iter()
+                .skip(1)
+                .take(n_neighbors)
+                .map(|(j, _)| j)
+                .collect()
+        })
+        .collect()
+}
+
+pub fn compactness<F: Float>(data: &Data<F>, labeling: &Discrete) -> F {
+    let centroids = labeling.centroids(data);
+
+    data.records
+        .axis_iter(Axis(0))
+        .zip(labeling.indicators.iter())
+        .map(|(record, &c)| (&record - &centroids.row(c)).mapv_into(|x| x * x).sum().sqrt())
+        .sum()
+}
+
+// Handl & Knowles (2007): penalty 1/j when the j-th nearest neighbour lies in another cluster
+pub fn connectivity(labeling: &Discrete, neighbors: &[Vec<usize>]) -> f64 {
+    neighbors
+        .iter()
+        .enumerate()
+        .map(|(i, list)| list
+            .iter()
+            .enumerate()
+            .filter(|(_, &j)| labeling.indicators[i] != labeling.indicators[j])
+            .map(|(rank, _)| 1.0 / (rank + 1) as f64)
+            .sum::<f64>()
+        )
+        .sum()
+}
+
+fn evaluate<F: Float>(data: &Data<F>, labeling: &Discrete, objectives: &[Objective], neighbors: &[Vec<usize>]) -> Vec<f64> {
+    objectives
+        .iter()
+        .map(|objective| match objective {
+            Objective::Compactness => compactness(data, labeling).to_f64().unwrap_or(f64::INFINITY),
+            Objective::Sse => metric::sse(data, labeling).ok().and_then(|sse| sse.to_f64()).unwrap_or(f64::INFINITY),
+            Objective::Connectivity => connectivity(labeling, neighbors),
+            Objective::NClusters => labeling.cluster_sizes().iter().filter(|&&s| s > 0).count() as f64
+        })
+        .collect()
+}
+
+fn dominates(a: &[f64], b: &[f64]) -> bool {
+    a.iter().zip(b).all(|(x, y)| x <= y) && a.iter().zip(b).any(|(x, y)| x < y)
+}
+
+// Fast non-dominated sorting, returns the fronts as lists of indices
+fn non_dominated_sort(scores: &[Vec<f64>]) -> Vec<Vec<usize>> {
+    let n = scores.len();
+    let mut dominated_by = vec![Vec::new(); n];
+    let mut domination_count = vec![0usize; n];
+    let mut fronts = vec![Vec::new()];
+
+    for p in 0..n {
+        for q in 0..n {
+            if dominates(&scores[p], &scores[q]) {
+                dominated_by[p].push(q);
+            } else if dominates(&scores[q], &scores[p]) {
+                domination_count[p] += 1;
+            }
+        }
+
+        if domination_count[p] == 0 {
+            fronts[0].push(p);
+        }
+    }
+
+    let mut current = 0;
+
+    while !fronts[current].is_empty() {
+        let mut next = Vec::new();
+
+        for &p in fronts[current].iter() {
+            for &q in dominated_by[p].iter() {
+                domination_count[q] -= 1;
+                if domination_count[q] == 0 {
+                    next.push(q);
+                }
+            }
+        }
+
+        fronts.push(next);
+        current += 1;
+    }
+
+    fronts.pop();
+    fronts
+}
+
+fn crowding_distance(front: &[usize], scores: &[Vec<f64>]) -> Vec<f64> {
+    let mut distance = vec![0.0; front.len()];
+
+    if front.is_empty() {
+        return distance;
+    }
+
+    let columns = (0..scores[front[0]].len())
+        .map(|m| front.iter().map(|&i| scores[i][m]).collect::<Vec<_>>());
+
+    for value in columns {
+        let mut order = (0..front.len()).collect::<Vec<_>>();
+        order.sort_by(|&a, &b| value[a].total_cmp(&value[b]));
+
+        let first = value[order[0]];
+        let last = value[order[front.len() - 1]];
+
+        distance[order[0]] = f64::INFINITY;
+        distance[order[front.len() - 1]] = f64::INFINITY;
+
+        if last - first <= 0.0 {
+            continue;
+        }
+
+        for k in 1..front.len().saturating_sub(1) {
+            let gap = value[order[k + 1]] - value[order[k - 1]];
+            distance[order[k]] += gap / (last - first);
+        }
+    }
+
+    distance
+}
+
+// Rank and crowding distance of every individual
+fn rank(scores: &[Vec<f64>]) -> (Vec<usize>, Vec<f64>) {
+    let mut ranks = vec![0; scores.len()];
+    let mut crowding = vec![0.0; scores.len()];
+
+    for (r, front) in non_dominated_sort(scores).iter().enumerate() {
+        for (&i, d) in front.iter().zip(crowding_distance(front, scores)) {
+            ranks[i] = r;
+            crowding[i] = d;
+        }
+    }
+
+    (ranks, crowding)
+}
+
+fn tournament<R: Rng>(ranks: &[usize], crowding: &[f64], rng: &mut R) -> usize {
+    let a = rng.gen_range(0..ranks.len());
+    let b = rng.gen_range(0..ranks.len());
+
+    match (ranks[a], ranks[b]) {
+        (ra, rb) if ra < rb => a,
+        (ra, rb) if rb < ra => b,
+        _ if crowding[a] >= crowding[b] => a,
+        _ => b
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters, objectives: &[Objective]) -> Result<Front, ClusteringError> {
+    missing::check(data)?;
+
+    let Parameters {
+        n_classes,
+        n_agents,
+        max_iterations,
+        crossover_rate,
+        mutation_rate,
+        n_neighbors,
+        seed
+    } = params;
+
+    if objectives.len() < 2 {
+        return Err(ClusteringError::InvalidParameters("multi-objective clustering needs at least two objectives".into()));
+    }
+
+    if n_agents == 0 {
+        return Err(ClusteringError::DegeneratePopulation("population needs at least one agent".into()));
+    }
+
+    initialization::check_classes(data, n_classes)?;
+
+    let n_samples = data.records.nrows();
+    let mut rng = seeded_rng(seed);
+
+    let neighbors = neighbors(data, n_neighbors);
+
+    // Half of the population starts from k-means solutions with 2..=n_classes clusters
+    let mut population = (0..n_agents)
+        .map(|i| match i % 2 {
+            0 => {
+                let k = 2 + (i / 2) % n_classes.saturating_sub(1).max(1);
+                let centroids = kmeans::plus_plus(data, k.min(n_classes), &mut rng);
+                let centroids = kmeans::lloyd(data, &centroids, 5);
+                let labels = kmeans::assign(data, &centroids).indicators;
+                Discrete::from_prediction(labels, n_classes)
+            },
+            _ => {
+                let labels = (0..n_samples).map(|_| rng.gen_range(0..n_classes)).collect::<Array1<usize>>();
+                Discrete::from_prediction(labels, n_classes)
+            }
+        })
+        .collect::<Vec<_>>();
+
+    let mut scores = population
+        .iter()
+        .map(|labeling| evaluate(data, labeling, objectives, &neighbors))
+        .collect::<Vec<_>>();
+
+    for _ in 0..max_iterations {
+        let (ranks, crowding) = rank(&scores);
+
+        let offspring = (0..n_agents)
+            .map(|_| {
+                let a = &population[tournament(&ranks, &crowding, &mut rng)];
+                let b = &population[tournament(&ranks, &crowding, &mut rng)];
+
+                // Uniform crossover
+                let mut labels = match rng.gen::<f64>() < crossover_rate {
+                    true => a.indicators
+                        .iter()
+                        .zip(b.indicators.iter())
+                        .map(|(&x, &y)| if rng.gen::<bool>() { x } else { y })
+                        .collect::<Array1<usize>>(),
+                    false => a.indicators.clone()
+                };
+
+                // Neighbourhood-biased mutation, occasionally opening a new cluster
+                for i in 0..n_samples {
+                    if rng.gen::<f64>() < mutation_rate {
+                        labels[i] = match neighbors[i].choose(&mut rng) {
+                            Some(&j) if rng.gen::<f64>() < 0.9 => labels[j],
+                            _ => rng.gen_range(0..n_classes)
+                        };
+                    }
+                }
+
+                Discrete::from_prediction(labels, n_classes)
+            })
+            .collect::<Vec<_>>();
+
+        let offspring_scores = offspring
+            .iter()
+            .map(|labeling| evaluate(data, labeling, objectives, &neighbors))
+            .collect::<Vec<_>>();
+
+        population.extend(offspring);
+        scores.extend(offspring_scores);
+
+        // Elitist survival by front, ties on the last front broken by crowding distance
+        let mut survivors = Vec::with_capacity(n_agents);
+
+        for front in non_dominated_sort(&scores) {
+            if survivors.len() + front.len() <= n_agents {
+                survivors.extend(front);
+                continue;
+            }
+
+            let distance = crowding_distance(&front, &scores);
+            let mut order = (0..front.len()).collect::<Vec<_>>();
+            order.sort_by(|&a, &b| distance[b].total_cmp(&distance[a]));
+
+            survivors.extend(order.into_iter().take(n_agents - survivors.len()).map(|k| front[k]));
+            break;
+        }
+
+        population = survivors.iter().map(|&i| population[i].clone()).collect();
+        scores = survivors.iter().map(|&i| scores[i].clone()).collect();
+    }
+
+    let first = non_dominated_sort(&scores).into_iter().next().unwrap_or_default();
+
+    let mut labelings: Vec<Discrete> = Vec::new();
+    let mut front_scores: Vec<Vec<f64>> = Vec::new();
+
+    for i in first {
+        if front_scores.iter().any(|s| *s == scores[i]) {
+            continue;
+        }
+        labelings.push(population[i].clone().compacted());
+        front_scores.push(scores[i].clone());
+    }
+
+    Ok(Front { objectives: objectives.to_vec(), labelings, scores: front_scores })
+}
@@ -0,0 +1,58 @@
+use linfa::Float;
+use ndarray::{Array2, Zip};
+use rand::{distributions::Distribution, Rng};
+
+use super::solution::Fuzzy;
+use crate::{error::ClusteringError, utility::sampling::Levy};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Mutation {
+    None,
+    Levy { exponent: f64, scale: f64, probability: f64 }
+}
+
+impl Mutation {
+    pub fn apply<F: Float, R: Rng>(&self, agent: &mut Fuzzy<F>, best: &Array2<F>, rng: &mut R) {
+        let Mutation::Levy { exponent, scale, probability } = *self else {
+            return;
+        };
+
+        // Exponents outside the domain of Levy::new are rejected by validate
+        let Ok(levy) = Levy::new(exponent) else {
+            return;
+        };
+
+        if rng.gen::<f64>() < probability {
+            agent.distribution = levy_flight(&agent.distribution, best, &levy, scale, rng);
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        let Mutation::Levy { exponent, scale, probability } = *self else {
+            return Ok(());
+        };
+
+        Levy::new(exponent)?;
+
+        if !(scale.is_finite() && scale >= 0.0) {
+            return Err(ClusteringError::InvalidParameters(format!("mutation scale must be finite and non-negative, got {}", scale)));
+        }
+
+        if !(0.0..=1.0).contains(&probability) {
+            return Err(ClusteringError::InvalidParameters(format!("mutation probability must lie in [0, 1], got {}", probability)));
+        }
+
+        Ok(())
+    }
+}
+
+// Lévy flight relative to the best solution, as in cuckoo search (Yang & Deb, 2009)
+pub fn levy_flight<F: Float, R: Rng>(position: &Array2<F>, best: &Array2<F>, levy: &Levy, scale: f64, rng: &mut R) -> Array2<F> {
+    let mut result = position.to_owned();
+
+    Zip::from(&mut result)
+        .and(best)
+        .for_each(|x, &b| *x += F::cast(scale * levy.sample(rng)) * (*x - b));
+
+    result
+}
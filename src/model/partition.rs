@@ -0,0 +1,192 @@
+use linfa::Float;
+use ndarray::{Array1, Array2, Axis};
+use rand::{seq::SliceRandom, Rng};
+
+use super::{initialization, solution::Discrete};
+use crate::{error::ClusteringError, Data};
+
+// Hard labeling with per-cluster sums, so that the within-cluster sum of
+// squares can be updated in O(n_features) when a single sample moves
+#[derive(Debug, Clone)]
+pub struct Partition<'a, F = f64> {
+    data: &'a Data<F>,
+    labels: Vec<usize>,
+    sums: Array2<F>,
+    sizes: Vec<usize>,
+    sse: F
+}
+
+impl<'a, F: Float> Partition<'a, F> {
+    pub fn new(data: &'a Data<F>, labeling: &Discrete) -> Result<Self, ClusteringError> {
+        let records = &data.records;
+        let n_classes = labeling.n_classes;
+
+        if labeling.indicators.len() != records.nrows() {
+            return Err(ClusteringError::ShapeMismatch(format!(
+                "labeling has {} labels for {} samples",
+                labeling.indicators.len(),
+                records.nrows()
+            )));
+        }
+
+        if let Some(&label) = labeling.indicators.iter().find(|&&label| label >= n_classes) {
+            return Err(ClusteringError::InvalidParameters(format!(
+                "label {} is out of range for {} classes",
+                label,
+                n_classes
+            )));
+        }
+
+        let labels = labeling.indicators.to_vec();
+        let mut sums = Array2::<F>::zeros((n_classes, records.ncols()));
+        let mut sizes = vec![0usize; n_classes];
+
+        for (record, &label) in records.axis_iter(Axis(0)).zip(labels.iter()) {
+            let mut sum = sums.row_mut(label);
+            sum += &record;
+            sizes[label] += 1;
+        }
+
+        let sse = labels
+            .iter()
+            .enumerate()
+            .map(|(i, &label)| {
+                let centroid = sums.row(label).mapv(|x| x / F::cast(sizes[label]));
+                (&records.row(i) - &centroid).mapv_into(|x| x * x).sum()
+            })
+            .sum();
+
+        Ok(Partition { data, labels, sums, sizes, sse })
+    }
+
+    // Random labeling in which every cluster holds at least one sample
+    pub fn random<R: Rng>(data: &'a Data<F>, n_classes: usize, rng: &mut R) -> Result<Self, ClusteringError> {
+        initialization::check_classes(data, n_classes)?;
+
+        let n_samples = data.records.nrows();
+
+        let mut labels = (0..n_samples)
+            .map(|_| rng.gen_range(0..n_classes))
+            .collect::<Array1<usize>>();
+
+        let mut order = (0..n_samples).collect::<Vec<_>>();
+        order.shuffle(rng);
+
+        for (label, &i) in order.iter().take(n_classes).enumerate() {
+            labels[i] = label;
+        }
+
+        Self::new(data, &Discrete::from_prediction(labels, n_classes))
+    }
+
+    pub fn n_samples(&self) -> usize {
+        self.labels.len()
+    }
+
+    pub fn n_classes(&self) -> usize {
+        self.sizes.len()
+    }
+
+    pub fn label(&self, sample: usize) -> usize {
+        self.labels[sample]
+    }
+
+    pub fn sse(&self) -> F {
+        self.sse
+    }
+
+    fn squared_distance_to(&self, sample: usize, cluster: usize) -> F {
+        let size = F::cast(self.sizes[cluster]);
+
+        self.data.records
+            .row(sample)
+            .iter()
+            .zip(self.sums.row(cluster).iter())
+            .map(|(&x, &sum)| (x - sum / size).powi(2))
+            .sum()
+    }
+
+    // Change of the sum of squares after moving the sample to the target cluster,
+    // infinite when the move would empty its current cluster
+    pub fn delta(&self, sample: usize, target: usize) -> F {
+        let source = self.labels[sample];
+
+        if source == target {
+            return F::zero();
+        }
+
+        let n_source = F::cast(self.sizes[source]);
+        let n_target = F::cast(self.sizes[target]);
+
+        if self.sizes[source] <= 1 {
+            return F::infinity();
+        }
+
+        let gain = match self.sizes[target] {
+            0 => F::zero(),
+            _ => n_target / (n_target + F::one()) * self.squared_distance_to(sample, target)
+        };
+
+        gain - n_source / (n_source - F::one()) * self.squared_distance_to(sample, source)
+    }
+
+    pub fn apply(&mut self, sample: usize, target: usize) {
+        let source = self.labels[sample];
+
+        if source == target {
+            return;
+        }
+
+        self.sse += self.delta(sample, target);
+
+        let record = self.data.records.row(sample);
+
+        let mut sum = self.sums.row_mut(source);
+        sum -= &record;
+        let mut sum = self.sums.row_mut(target);
+        sum += &record;
+
+        self.sizes[source] -= 1;
+        self.sizes[target] += 1;
+        self.labels[sample] = target;
+    }
+
+    pub fn to_discrete(&self) -> Discrete {
+        Discrete::from_prediction(Array1::from_vec(self.labels.clone()), self.n_classes())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use ndarray::array;
+
+    use super::*;
+
+    #[test]
+    fn rejects_invalid_labelings() {
+        let data = linfa_datasets::iris();
+
+        let short = Discrete::from_prediction(array![0, 1, 0], 2);
+        assert!(matches!(Partition::new(&data, &short), Err(ClusteringError::ShapeMismatch(_))));
+
+        let out_of_range = Discrete::from_prediction(Array1::from_elem(data.records.nrows(), 2), 2);
+        assert!(matches!(Partition::new(&data, &out_of_range), Err(ClusteringError::InvalidParameters(_))));
+    }
+
+    #[test]
+    fn incremental_sse_matches_recomputed() {
+        let data = linfa_datasets::iris();
+        let mut rng = crate::utility::sampling::seeded_rng(Some(3));
+        let mut partition = Partition::random(&data, 3, &mut rng).unwrap();
+
+        for sample in 0..20 {
+            let target = (partition.label(sample) + 1) % 3;
+            let expected = partition.sse() + partition.delta(sample, target);
+            partition.apply(sample, target);
+
+            let recomputed = Partition::new(&data, &partition.to_discrete()).unwrap().sse();
+            assert!((partition.sse() - recomputed).abs() < 1e-9);
+            assert!((expected - recomputed).abs() < 1e-9);
+        }
+    }
+}
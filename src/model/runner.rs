@@ -0,0 +1,139 @@
+use std::cmp::Ordering;
+
+use linfa::Float;
+use rand::rngs::StdRng;
+
+use super::{distance::Measure, history::History, initialization::{self, Initialization}, memetic::Refinement, solution::Fuzzy};
+use crate::{error::ClusteringError, utility::sampling::seeded_rng, Data};
+
+// Settings shared by the population-based optimizers over fuzzy memberships
+#[derive(Debug, Clone, Copy)]
+pub struct Settings {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub seed: Option<u64>
+}
+
+impl Settings {
+    // Checks that do not depend on the data; the number of classes is compared
+    // with the number of samples when the population is initialized
+    pub fn validate(&self, min_agents: usize) -> Result<(), ClusteringError> {
+        if self.n_classes == 0 {
+            return Err(ClusteringError::InvalidParameters("number of classes must be positive".into()));
+        }
+
+        if self.n_agents < min_agents.max(1) {
+            return Err(ClusteringError::InvalidParameters(format!(
+                "population needs at least {} agents, got {}",
+                min_agents.max(1),
+                self.n_agents
+            )));
+        }
+
+        if self.max_iterations == 0 {
+            return Err(ClusteringError::InvalidParameters("number of iterations must be positive".into()));
+        }
+
+        self.metric.validate()?;
+        self.refinement.validate(self.metric)
+    }
+}
+
+// Agents with their cached fitness, and the best agent found so far
+#[derive(Debug, Clone)]
+pub struct Population<F> {
+    pub agents: Vec<Fuzzy<F>>,
+    pub fitness: Vec<F>,
+    pub best: Fuzzy<F>,
+    pub best_fitness: F
+}
+
+impl<F: Float> Population<F> {
+    fn new(agents: Vec<Fuzzy<F>>, data: &Data<F>, metric: Measure) -> Self {
+        let fitness = agents
+            .iter()
+            .map(|agent| agent.fitness_with(data, &metric))
+            .collect::<Vec<_>>();
+
+        let best = agents[0].clone();
+        let mut population = Population { agents, fitness, best, best_fitness: F::neg_infinity() };
+        population.update_best();
+        population
+    }
+
+    // Index of the fittest current agent
+    pub fn fittest(&self) -> usize {
+        (0..self.fitness.len())
+            .max_by(|&a, &b| self.fitness[a].partial_cmp(&self.fitness[b]).unwrap_or(Ordering::Equal))
+            .unwrap_or(0)
+    }
+
+    // Re-evaluates an agent the step moved without its fitness
+    pub fn evaluate(&mut self, i: usize, data: &Data<F>, metric: Measure) {
+        self.fitness[i] = self.agents[i].fitness_with(data, &metric);
+    }
+
+    fn update_best(&mut self) {
+        let i = self.fittest();
+
+        if self.fitness[i] > self.best_fitness {
+            self.best_fitness = self.fitness[i];
+            self.best = self.agents[i].clone();
+        }
+    }
+}
+
+// Iterates the step, which moves the agents and keeps their fitness up to
+// date, then applies the periodic refinement, records the history and returns
+// the best agent found, refined at the end if requested
+pub fn run<F, S>(data: &Data<F>, settings: Settings, mut history: Option<&mut History>, mut step: S) -> Result<Fuzzy<F>, ClusteringError>
+where
+    F: Float,
+    S: FnMut(&mut Population<F>, usize, &mut StdRng) -> Result<(), ClusteringError>
+{
+    let Settings {
+        n_classes,
+        n_agents,
+        max_iterations,
+        metric,
+        initialization,
+        refinement,
+        seed
+    } = settings;
+
+    let mut rng = seeded_rng(seed);
+
+    let agents = initialization::population(data, n_agents, n_classes, initialization, metric, &mut rng)?;
+    let mut population = Population::new(agents, data, metric);
+
+    for time in 0..max_iterations {
+        step(&mut population, time, &mut rng)?;
+
+        if refinement.is_due(time) {
+            refinement.refine_best(data, &mut population.agents, &mut population.fitness, &metric);
+        }
+
+        population.update_best();
+
+        if let Some(history) = history.as_deref_mut() {
+            history.record(&population.fitness);
+        }
+    }
+
+    Ok(refinement.finalize(data, population.best, &metric))
+}
+
+// Runs an optimizer while recording the fitness of its population after every iteration
+pub fn with_history<T, R>(run: R) -> Result<(T, History), ClusteringError>
+where
+    R: FnOnce(Option<&mut History>) -> Result<T, ClusteringError>
+{
+    let mut history = History::new();
+    let best = run(Some(&mut history))?;
+
+    Ok((best, history))
+}
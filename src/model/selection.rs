@@ -0,0 +1,174 @@
+use std::{cmp::Ordering, ops::RangeInclusive};
+
+use linfa::{dataset::Records, DatasetBase, Float};
+use ndarray::{Array1, Array2, Axis};
+use ndarray_rand::{rand_distr::Uniform, RandomExt};
+
+use super::{metric, solution::Discrete};
+use crate::{error::ClusteringError, utility::sampling::seeded_rng, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub enum Criterion {
+    Elbow,
+    Silhouette,
+    Gap { n_references: usize, seed: Option<u64> },
+    Bic
+}
+
+#[derive(Debug, Clone)]
+pub struct Selection<F = f64> {
+    pub k: usize,
+    pub ks: Vec<usize>,
+    pub scores: Vec<F>,
+    // Standard errors s_k of the gap statistic, None for the other criteria
+    pub deviations: Option<Vec<F>>
+}
+
+pub fn select_k<F, Fit>(
+    data: &Data<F>,
+    ks: RangeInclusive<usize>,
+    criterion: Criterion,
+    fit: Fit
+) -> Result<Selection<F>, ClusteringError>
+where
+    F: Float,
+    Fit: Fn(&Data<F>, usize) -> Result<Discrete, ClusteringError>
+{
+    let ks = ks.collect::<Vec<_>>();
+
+    if ks.is_empty() {
+        return Err(ClusteringError::InvalidParameters("empty range of k".into()));
+    }
+
+    let predictions = ks
+        .iter()
+        .map(|&k| fit(data, k))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let selection = match criterion {
+        Criterion::Elbow => {
+            let scores = predictions
+                .iter()
+                .map(|prediction| metric::sse(data, prediction))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Selection { k: ks[elbow(&scores)], ks, scores, deviations: None }
+        },
+        Criterion::Silhouette => {
+            let scores = predictions
+                .iter()
+                .map(|prediction| metric::silhouette(data, prediction))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Selection { k: ks[argmax(&scores)], ks, scores, deviations: None }
+        },
+        Criterion::Bic => {
+            let scores = predictions
+                .iter()
+                .map(|prediction| metric::bic(data, prediction))
+                .collect::<Result<Vec<_>, _>>()?;
+
+            Selection { k: ks[argmax(&scores)], ks, scores, deviations: None }
+        },
+        Criterion::Gap { n_references, seed } => gap(data, ks, &predictions, n_references, seed, &fit)?
+    };
+
+    Ok(selection)
+}
+
+// Point of maximum distance below the chord joining the first and last score (Kneedle)
+fn elbow<F: Float>(scores: &[F]) -> usize {
+    let n = scores.len();
+
+    if n < 3 {
+        return 0;
+    }
+
+    let (first, last) = (scores[0], scores[n - 1]);
+    let range = match (first - last).abs() {
+        r if r > F::zero() => r,
+        _ => return 0
+    };
+
+    (0..n)
+        .map(|i| {
+            let x = F::cast(i) / F::cast(n - 1);
+            let y = (scores[i] - last) / range;
+            (i, (F::one() - x) - y)
+        })
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+fn argmax<F: Float>(scores: &[F]) -> usize {
+    scores
+        .iter()
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+// Tibshirani et al. (2001): smallest k with Gap(k) >= Gap(k + 1) - s(k + 1)
+fn gap<F, Fit>(
+    data: &Data<F>,
+    ks: Vec<usize>,
+    predictions: &[Discrete],
+    n_references: usize,
+    seed: Option<u64>,
+    fit: &Fit
+) -> Result<Selection<F>, ClusteringError>
+where
+    F: Float,
+    Fit: Fn(&Data<F>, usize) -> Result<Discrete, ClusteringError>
+{
+    let records = data.records();
+    let n_references = n_references.max(1);
+    let mut rng = seeded_rng(seed);
+
+    let lower = records.fold_axis(Axis(0), F::infinity(), |&a, &b| a.min(b));
+    let upper = records.fold_axis(Axis(0), F::neg_infinity(), |&a, &b| a.max(b));
+    let span = &upper - &lower;
+
+    let references = (0..n_references)
+        .map(|_| {
+            let unit = Array2::random_using(records.dim(), Uniform::new(F::zero(), F::one()), &mut rng);
+            let records = unit * &span + &lower;
+            DatasetBase::new(records, Array1::<usize>::zeros(data.nsamples()))
+        })
+        .collect::<Vec<Data<F>>>();
+
+    // A zero SSE, e.g. with as many clusters as distinct records, has no logarithm
+    let log_sse = |data: &Data<F>, prediction: &Discrete| match metric::sse(data, prediction)? {
+        sse if sse > F::zero() => Ok(sse.ln()),
+        _ => Err(ClusteringError::Numerical(format!("gap statistic needs a positive SSE, got zero for k = {}", prediction.n_classes)))
+    };
+
+    let mut scores = Vec::with_capacity(ks.len());
+    let mut deviations = Vec::with_capacity(ks.len());
+
+    for (&k, prediction) in ks.iter().zip(predictions) {
+        let observed = log_sse(data, prediction)?;
+
+        let expected = references
+            .iter()
+            .map(|reference| {
+                log_sse(reference, &fit(reference, k)?)
+            })
+            .collect::<Result<Vec<F>, ClusteringError>>()?;
+
+        let n = F::cast(n_references);
+        let mean = expected.iter().fold(F::zero(), |acc, &e| acc + e) / n;
+        let std = (expected.iter().fold(F::zero(), |acc, &e| acc + (e - mean) * (e - mean)) / n).sqrt();
+
+        scores.push(mean - observed);
+        deviations.push(std * (F::one() + F::one() / n).sqrt());
+    }
+
+    let index = (0..ks.len() - 1)
+        .find(|&i| scores[i] >= scores[i + 1] - deviations[i + 1])
+        .unwrap_or(ks.len() - 1);
+
+    Ok(Selection { k: ks[index], ks, scores, deviations: Some(deviations) })
+}
@@ -0,0 +1,155 @@
+use std::f64::consts;
+
+use linfa::{Float, ParamGuard};
+use ndarray::{Array2, Zip};
+use ndarray_rand::{rand_distr::Uniform, RandomExt};
+
+use super::{distance::Measure, history::History, initialization::Initialization, memetic::Refinement, runner::{self, Settings}, solution::Fuzzy};
+use crate::{error::ClusteringError, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub amplitude: f64,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn builder(n_classes: usize) -> ParametersBuilder {
+        ParametersBuilder(Parameters {
+            n_classes,
+            n_agents: 20,
+            max_iterations: 500,
+            amplitude: 2.0,
+            metric: Measure::Euclidean,
+            initialization: Initialization::Random,
+            refinement: Refinement::None,
+            seed: None
+        })
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            n_classes: self.n_classes,
+            n_agents: self.n_agents,
+            max_iterations: self.max_iterations,
+            metric: self.metric,
+            initialization: self.initialization,
+            refinement: self.refinement,
+            seed: self.seed
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        if !(self.amplitude.is_finite() && self.amplitude > 0.0) {
+            return Err(ClusteringError::InvalidParameters(format!("amplitude must be finite and positive, got {}", self.amplitude)));
+        }
+
+        self.settings().validate(1)
+    }
+}
+
+// Unchecked parameters with defaults, turned into Parameters by ParamGuard::check
+#[derive(Debug, Clone, Copy)]
+pub struct ParametersBuilder(Parameters);
+
+impl ParametersBuilder {
+    pub fn n_agents(mut self, n_agents: usize) -> Self {
+        self.0.n_agents = n_agents;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.0.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn amplitude(mut self, amplitude: f64) -> Self {
+        self.0.amplitude = amplitude;
+        self
+    }
+
+    pub fn metric(mut self, metric: Measure) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    pub fn initialization(mut self, initialization: Initialization) -> Self {
+        self.0.initialization = initialization;
+        self
+    }
+
+    pub fn refinement(mut self, refinement: Refinement) -> Self {
+        self.0.refinement = refinement;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.0.seed = Some(seed);
+        self
+    }
+}
+
+impl ParamGuard for ParametersBuilder {
+    type Checked = Parameters;
+    type Error = ClusteringError;
+
+    fn check_ref(&self) -> Result<&Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(&self.0)
+    }
+
+    fn check(self) -> Result<Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    runner::with_history(|history| run(data, params, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    params.validate()?;
+
+    let n_samples = data.records.nrows();
+    let Parameters { n_classes, max_iterations, amplitude, metric, .. } = params;
+
+    // The destination point is the best agent found so far
+    runner::run(data, params.settings(), history, |population, time, rng| {
+        let step = F::cast(amplitude - amplitude * time as f64 / max_iterations as f64);
+        let destination = &population.best.distribution;
+
+        for (i, agent) in population.agents.iter_mut().enumerate() {
+            let phase = Array2::random_using((n_samples, n_classes), Uniform::new(F::zero(), F::cast(2.0 * consts::PI)), rng);
+            let weight = Array2::random_using((n_samples, n_classes), Uniform::new(F::zero(), F::cast(2.0)), rng);
+            let switch = Array2::random_using((n_samples, n_classes), Uniform::new(F::zero(), F::one()), rng);
+
+            Zip::from(&mut agent.distribution)
+                .and(destination)
+                .and(&phase)
+                .and(&weight)
+                .and(&switch)
+                .for_each(|x, &p, &phase, &weight, &switch| {
+                    let oscillation = match switch < F::cast(0.5) {
+                        true => phase.sin(),
+                        false => phase.cos()
+                    };
+                    *x += step * oscillation * (weight * p - *x).abs();
+                });
+
+            population.fitness[i] = agent.fitness_with(data, &metric);
+        }
+
+        Ok(())
+    })
+}
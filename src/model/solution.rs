@@ -1,25 +1,26 @@
-use std::collections::HashMap;
+use std::{cmp::Ordering, collections::HashMap};
 
-use linfa::dataset::Records;
-use ndarray::{Array1, Array2, ArrayBase, Axis, OwnedRepr};
+use linfa::{dataset::Records, Float};
+use ndarray::{Array1, Array2, Axis};
 use ndarray_rand::rand_distr::Uniform;
 use ndarray_rand::RandomExt;
-use ndarray_stats::{errors::{MinMaxError, MultiInputError}, DeviationExt, QuantileExt};
 use itertools::Itertools;
 use pathfinding::prelude::{kuhn_munkres, Matrix};
+use rand::{seq::index, Rng};
 
-use crate::Data;
+use super::{distance::{agent::Distance, Euclidean, Metric, SquaredEuclidean}, metric};
+use crate::{error::ClusteringError, Data};
 
 #[derive(Debug, Clone)]
-pub struct Fuzzy {
-    pub distribution: Array2<f64>,
+pub struct Fuzzy<F = f64> {
+    pub distribution: Array2<F>,
     pub n_samples: usize,
     pub n_classes: usize,
 }
 
-impl Fuzzy {
+impl<F: Float> Fuzzy<F> {
     pub fn random(n_samples: usize, n_classes: usize) -> Self {
-        let distribution = Array2::random((n_samples, n_classes), Uniform::new(0.0, 1.0));
+        let distribution = Array2::random((n_samples, n_classes), Uniform::new(F::zero(), F::one()));
         Fuzzy {
             distribution,
             n_samples,
@@ -27,83 +28,112 @@ impl Fuzzy {
         }
     }
 
-    pub fn l2_distance(a: &Fuzzy, b: &Fuzzy) -> Result<f64, MultiInputError> {
-        a.distribution.l2_dist(&b.distribution)
+    pub fn random_using<R: Rng>(n_samples: usize, n_classes: usize, rng: &mut R) -> Self {
+        let distribution = Array2::random_using((n_samples, n_classes), Uniform::new(F::zero(), F::one()), rng);
+        Fuzzy {
+            distribution,
+            n_samples,
+            n_classes,
+        }
+    }
+
+    // Fuzzy c-means memberships (fuzzifier 2) of every sample to the given centroids
+    pub fn from_centroids(data: &Data<F>, centroids: &Array2<F>) -> Self {
+        Self::from_centroids_with(data, centroids, &SquaredEuclidean)
     }
 
-    pub fn cosine_distance(a: &Fuzzy, b: &Fuzzy) -> Result<f64, MultiInputError> {
-        let a = &a.distribution;
-        let b = &b.distribution;
+    // Memberships inversely proportional to the distance to each centroid
+    pub fn from_centroids_with<M: Metric<F> + ?Sized>(data: &Data<F>, centroids: &Array2<F>, metric: &M) -> Self {
+        let samples = data.records();
+        let n_samples = samples.nrows();
+        let n_classes = centroids.nrows();
 
-        let a_norm = a.sq_l2_dist(a)?;
-        let b_norm = b.sq_l2_dist(b)?;
-        let dist = a.sq_l2_dist(b)?;
+        let mut distribution = Array2::<F>::zeros((n_samples, n_classes));
 
-        Ok(dist / (a_norm * b_norm))
+        for (mut row, record) in distribution.axis_iter_mut(Axis(0)).zip(samples.axis_iter(Axis(0))) {
+            for (membership, centroid) in row.iter_mut().zip(centroids.axis_iter(Axis(0))) {
+                *membership = F::one() / (metric.distance(record, centroid) + F::epsilon());
+            }
+
+            let total = row.sum();
+            row.mapv_inplace(|x| x / total);
+        }
+
+        Fuzzy { distribution, n_samples, n_classes }
     }
 
-    pub fn fitness(&self, data: &Data) -> f64 {
-        let samples = data.records();
-        let n_cols = samples.ncols();
+    pub fn centroids(&self, data: &Data<F>) -> Array2<F> {
+        Discrete::from_prediction(hard_labels(&self.distribution), self.n_classes).centroids(data)
+    }
 
-        let indicator = self
-            .clone()
-            .to_discrete()
-            .to_vec();
+    pub fn centroids_with<M: Metric<F> + ?Sized>(&self, data: &Data<F>, metric: &M) -> Array2<F> {
+        Discrete::from_prediction(hard_labels(&self.distribution), self.n_classes).centroids_with(data, metric)
+    }
 
-        let clusters = indicator
-            .iter()
-            .zip(samples.axis_iter(Axis(0)))
-            .into_group_map();
+    pub fn l2_distance(a: &Fuzzy<F>, b: &Fuzzy<F>) -> Result<F, ClusteringError> {
+        Distance::L2.measure(&a.distribution, &b.distribution)
+    }
 
-        let centroids: HashMap<_, _> = clusters
-            .iter()
-            .map(|(&cluster_num, records)| {
-                let n_samples = records.len() as f64;
-                let centroid = records.iter().fold(
-                    Array1::zeros(n_cols),
-                    |acc: ArrayBase<OwnedRepr<f64>, _>, record| acc + record
-                ) / n_samples;
-
-                (cluster_num, centroid)
-            })
-            .collect();
+    pub fn cosine_distance(a: &Fuzzy<F>, b: &Fuzzy<F>) -> Result<F, ClusteringError> {
+        Distance::Cosine.measure(&a.distribution, &b.distribution)
+    }
 
-        let variance = centroids
-            .iter()
-            .zip(clusters)
-            .map(|((_, centroid), (_, records))| records
-                .iter()
-                .map(|record| (record - centroid)
-                    .mapv_into(|x| x.powf(2.0))
-                    .sum()
-                    .sqrt()
-                )
-                .sum::<f64>()
-            )
-            .sum::<f64>();
+    // Inverse of the total distance between the records and their cluster centroids
+    pub fn fitness(&self, data: &Data<F>) -> F {
+        self.fitness_with(data, &Euclidean)
+    }
 
-        1.0 / variance
+    // Agents with NaN memberships get the lowest possible fitness of zero
+    pub fn fitness_with<M: Metric<F> + ?Sized>(&self, data: &Data<F>, metric: &M) -> F {
+        if check_finite(&self.distribution).is_err() {
+            return F::zero();
+        }
+
+        Discrete::from_prediction(hard_labels(&self.distribution), self.n_classes).fitness_with(data, metric)
+    }
+
+    pub fn to_prob(self) -> Result<Probabilistic<F>, ClusteringError> {
+        self.try_into()
     }
 
-    pub fn to_prob(self) -> Probabilistic {
-        self.try_into().unwrap()
+    pub fn to_discrete(self) -> Result<Discrete, ClusteringError> {
+        self.try_into()
     }
+}
+
+// Most likely cluster of every sample, the first one on ties; NaN memberships are never chosen
+fn hard_labels<F: Float>(distribution: &Array2<F>) -> Array1<usize> {
+    distribution.map_axis(Axis(1), |row| {
+        let mut best = 0;
+
+        for (i, &x) in row.iter().enumerate() {
+            if !x.is_nan() && (row[best].is_nan() || x > row[best]) {
+                best = i;
+            }
+        }
+
+        best
+    })
+}
 
-    pub fn to_discrete(self) -> Discrete {
-        self.try_into().unwrap()
+fn check_finite<F: Float>(distribution: &Array2<F>) -> Result<(), ClusteringError> {
+    match distribution.iter().any(|x| x.is_nan()) {
+        true => Err(ClusteringError::NanEncountered("membership matrix contains NaN".into())),
+        false => Ok(())
     }
 }
 
-impl TryInto<Probabilistic> for Fuzzy {
-    type Error = MinMaxError;
+impl<F: Float> TryFrom<Fuzzy<F>> for Probabilistic<F> {
+    type Error = ClusteringError;
+
+    fn try_from(fuzzy: Fuzzy<F>) -> Result<Probabilistic<F>, ClusteringError> {
+        let Fuzzy { mut distribution, n_classes, n_samples } = fuzzy;
 
-    fn try_into(self) -> Result<Probabilistic, MinMaxError> {
-        let Fuzzy { mut distribution, n_classes, n_samples } = self;
+        check_finite(&distribution)?;
 
         for mut row in distribution.axis_iter_mut(Axis(0)) {
-            let max = *row.max()?;
-            row.mapv_inplace(|x| f64::exp(x - max));
+            let max = row.fold(F::neg_infinity(), |a, &b| a.max(b));
+            row.mapv_inplace(|x| (x - max).exp());
 
             let sum = row.sum();
             row.mapv_inplace(|x| x / sum);
@@ -113,46 +143,40 @@ impl TryInto<Probabilistic> for Fuzzy {
     }
 }
 
-impl TryInto<Discrete> for Fuzzy {
-    type Error = MinMaxError;
+impl<F: Float> TryFrom<Fuzzy<F>> for Discrete {
+    type Error = ClusteringError;
 
-    fn try_into(self) -> Result<Discrete, Self::Error> {
-        let Fuzzy { distribution, n_classes, n_samples } = self;
+    fn try_from(fuzzy: Fuzzy<F>) -> Result<Discrete, ClusteringError> {
+        check_finite(&fuzzy.distribution)?;
 
-        let indicators = distribution.map_axis(
-            Axis(1),
-            |row| row.argmax().unwrap(), // TODO handle error properly
-        );
+        let Fuzzy { distribution, n_classes, n_samples } = fuzzy;
 
-        Ok(Discrete { indicators, n_classes, n_samples })
+        Ok(Discrete { indicators: hard_labels(&distribution), n_classes, n_samples })
     }
 }
 
 #[derive(Debug, Clone)]
-pub struct Probabilistic {
-    pub distribution: Array2<f64>,
+pub struct Probabilistic<F = f64> {
+    pub distribution: Array2<F>,
     pub n_samples: usize,
     pub n_classes: usize,
 }
 
-impl Probabilistic {
-    pub fn to_discrete(self) -> Discrete {
-        self.try_into().unwrap()
+impl<F: Float> Probabilistic<F> {
+    pub fn to_discrete(self) -> Result<Discrete, ClusteringError> {
+        self.try_into()
     }
 }
 
-impl TryInto<Discrete> for Probabilistic {
-    type Error = MinMaxError;
+impl<F: Float> TryFrom<Probabilistic<F>> for Discrete {
+    type Error = ClusteringError;
 
-    fn try_into(self) -> Result<Discrete, Self::Error> {
-        let Probabilistic { distribution, n_classes, n_samples } = self;
+    fn try_from(probabilistic: Probabilistic<F>) -> Result<Discrete, ClusteringError> {
+        check_finite(&probabilistic.distribution)?;
 
-        let indicators = distribution.map_axis(
-            Axis(1),
-            |row| row.argmax().unwrap(), // TODO handle error properly
-        );
+        let Probabilistic { distribution, n_classes, n_samples } = probabilistic;
 
-        Ok(Discrete { indicators, n_classes, n_samples })
+        Ok(Discrete { indicators: hard_labels(&distribution), n_classes, n_samples })
     }
 }
 
@@ -164,7 +188,7 @@ pub struct Discrete {
 }
 
 impl Discrete {
-    pub fn new(data: &Data) -> Self {
+    pub fn new<F: Float>(data: &Data<F>) -> Self {
         let indicators = data.targets().to_owned();
         let n_samples = data.nsamples();
         let n_classes = data
@@ -186,28 +210,123 @@ impl Discrete {
         self.indicators.to_vec()
     }
 
-    pub fn matched_with(self, truth: &Discrete) -> Result<Discrete, ndarray::ErrorKind> {
+    pub fn centroids<F: Float>(&self, data: &Data<F>) -> Array2<F> {
+        let samples = data.records();
+        let mut centroids = Array2::<F>::zeros((self.n_classes, samples.ncols()));
+        let mut counts = vec![0usize; self.n_classes];
+
+        for (&cluster, record) in self.indicators.iter().zip(samples.axis_iter(Axis(0))) {
+            let mut centroid = centroids.row_mut(cluster);
+            centroid += &record;
+            counts[cluster] += 1;
+        }
+
+        for (mut centroid, count) in centroids.axis_iter_mut(Axis(0)).zip(counts) {
+            if count > 0 {
+                centroid /= F::cast(count);
+            }
+        }
+
+        centroids
+    }
+
+    // Centers of the clusters under the metric, the mean for metrics without one;
+    // empty clusters get a zero centroid
+    pub fn centroids_with<F: Float, M: Metric<F> + ?Sized>(&self, data: &Data<F>, metric: &M) -> Array2<F> {
+        let samples = data.records();
+        let mut centroids = Array2::<F>::zeros((self.n_classes, samples.ncols()));
+
+        for (k, members) in self.members().into_iter().enumerate().filter(|(_, members)| !members.is_empty()) {
+            let records = samples.select(Axis(0), &members);
+            let center = metric.center(records.view()).or_else(|| records.mean_axis(Axis(0)));
+
+            if let Some(center) = center {
+                centroids.row_mut(k).assign(&center);
+            }
+        }
+
+        centroids
+    }
+
+    // Same fitness as the one of a fuzzy agent with these hard labels
+    pub fn fitness<F: Float>(&self, data: &Data<F>) -> F {
+        self.fitness_with(data, &Euclidean)
+    }
+
+    pub fn fitness_with<F: Float, M: Metric<F> + ?Sized>(&self, data: &Data<F>, metric: &M) -> F {
+        let centroids = self.centroids_with(data, metric);
+
+        let variance = data
+            .records()
+            .axis_iter(Axis(0))
+            .zip(self.indicators.iter())
+            .map(|(record, &cluster)| metric.distance(record, centroids.row(cluster)))
+            .sum::<F>();
+
+        variance.recip()
+    }
+
+    // Sample indices of every cluster
+    pub fn members(&self) -> Vec<Vec<usize>> {
+        let mut members = vec![Vec::new(); self.n_classes];
+
+        for (i, &cluster) in self.indicators.iter().enumerate() {
+            members[cluster].push(i);
+        }
+
+        members
+    }
+
+    // Relabels the non-empty clusters to 0..k, preserving their order
+    pub fn compacted(self) -> Discrete {
+        let mapping: HashMap<usize, usize> = self.indicators
+            .iter()
+            .cloned()
+            .unique()
+            .sorted()
+            .enumerate()
+            .map(|(label, cluster)| (cluster, label))
+            .collect();
+
+        let indicators = self.indicators.mapv(|cluster| mapping[&cluster]);
+
+        Discrete::from_prediction(indicators, mapping.len().max(1))
+    }
+
+    pub fn cluster_sizes(&self) -> Vec<usize> {
+        let mut counts = vec![0usize; self.n_classes];
+        self.indicators.iter().for_each(|&cluster| counts[cluster] += 1);
+        counts
+    }
+
+    // Relabels the clusters to agree with the truth as much as possible (Hungarian method)
+    pub fn matched_with(self, truth: &Discrete) -> Result<Discrete, ClusteringError> {
         let Discrete { mut indicators, n_classes, n_samples } = self;
-        
+
         if n_samples != truth.indicators.dim() {
-            return Err(ndarray::ErrorKind::IncompatibleShape);
+            return Err(ClusteringError::ShapeMismatch("labels and records differ in length".into()));
         }
 
-        let mut cost = Matrix::new(n_classes, n_classes, 0isize);
+        let size = truth.indicators
+            .iter()
+            .chain(indicators.iter())
+            .map(|&label| label + 1)
+            .fold(n_classes.max(truth.n_classes), usize::max);
+
+        // Rows are predicted clusters, columns true classes
+        let mut overlap = Matrix::new(size, size, 0isize);
 
         truth
             .indicators
             .iter()
             .zip(indicators.iter())
-            .for_each(|(&t, &p)| unsafe {
-                *cost.get_unchecked_mut(t * n_classes + p) += 1
-            });
+            .for_each(|(&t, &p)| overlap[(p, t)] += 1);
 
-        let (_, mapping) = kuhn_munkres(&cost);
+        let (_, mapping) = kuhn_munkres(&overlap);
 
-        indicators.mapv_inplace(|x| mapping[x]);
+        indicators.mapv_inplace(|p| mapping[p]);
 
-        Ok(Discrete { indicators, n_classes, n_samples })
+        Ok(Discrete { indicators, n_classes: size, n_samples })
     }
 }
 
@@ -216,3 +335,126 @@ impl Into<Vec<usize>> for Discrete {
         self.to_vec()
     }
 }
+
+// Variable-k encoding (Das et al., 2008): k_max candidate centroids, each
+// switched on when its activation exceeds the threshold.
+#[derive(Debug, Clone)]
+pub struct Activated<F = f64> {
+    pub centroids: Array2<F>,
+    pub activations: Array1<F>,
+    pub threshold: F,
+}
+
+impl<F: Float> Activated<F> {
+    pub fn random_using<R: Rng>(data: &Data<F>, k_max: usize, threshold: F, rng: &mut R) -> Self {
+        let (lower, upper) = Self::bounds(data);
+        let unit = Array2::random_using((k_max, data.nfeatures()), Uniform::new(F::zero(), F::one()), rng);
+
+        let centroids = unit * &(&upper - &lower) + &lower;
+        let activations = Array1::random_using(k_max, Uniform::new(F::zero(), F::one()), rng);
+
+        Activated { centroids, activations, threshold }
+    }
+
+    pub fn bounds(data: &Data<F>) -> (Array1<F>, Array1<F>) {
+        let records = data.records();
+        let lower = records.fold_axis(Axis(0), F::infinity(), |&a, &b| a.min(b));
+        let upper = records.fold_axis(Axis(0), F::neg_infinity(), |&a, &b| a.max(b));
+        (lower, upper)
+    }
+
+    pub fn k_max(&self) -> usize {
+        self.activations.len()
+    }
+
+    pub fn active(&self) -> Vec<usize> {
+        (0..self.k_max())
+            .filter(|&i| self.activations[i] > self.threshold)
+            .collect()
+    }
+
+    // Keeps the agent inside the search box and switches on random centroids
+    // until at least k_min are active
+    pub fn repair<R: Rng>(&mut self, data: &Data<F>, k_min: usize, rng: &mut R) {
+        let (lower, upper) = Self::bounds(data);
+
+        for mut centroid in self.centroids.axis_iter_mut(Axis(0)) {
+            centroid.zip_mut_with(&lower, |x, &l| *x = x.max(l));
+            centroid.zip_mut_with(&upper, |x, &u| *x = x.min(u));
+        }
+
+        self.activations.mapv_inplace(|a| a.max(F::zero()).min(F::one()));
+
+        let k_min = k_min.min(self.k_max());
+        let active = self.active().len();
+
+        if active < k_min {
+            let inactive = (0..self.k_max())
+                .filter(|&i| self.activations[i] <= self.threshold)
+                .collect::<Vec<_>>();
+
+            // Activations in (threshold, 1], so every switched centroid counts as active
+            for i in index::sample(rng, inactive.len(), k_min - active) {
+                self.activations[inactive[i]] = F::one() - F::cast(rng.gen::<f64>()) * (F::one() - self.threshold);
+            }
+        }
+    }
+
+    // Checks the activation threshold and the range of the number of clusters
+    pub fn validate(k_min: usize, k_max: usize, threshold: F) -> Result<(), ClusteringError> {
+        if !(threshold > F::zero() && threshold < F::one()) {
+            return Err(ClusteringError::InvalidParameters(format!("activation threshold must lie in (0, 1), got {}", threshold)));
+        }
+
+        if k_min == 0 || k_min > k_max {
+            return Err(ClusteringError::InvalidParameters(format!("expected 1 <= k_min <= k_max, got k_min = {} and k_max = {}", k_min, k_max)));
+        }
+
+        Ok(())
+    }
+
+    pub fn to_discrete(&self, data: &Data<F>) -> Discrete {
+        self.to_discrete_with(data, &SquaredEuclidean)
+    }
+
+    // Every record goes to the nearest active centroid under the metric
+    pub fn to_discrete_with<M: Metric<F> + ?Sized>(&self, data: &Data<F>, metric: &M) -> Discrete {
+        let active = self.active();
+        let samples = data.records();
+
+        let nearest = samples
+            .axis_iter(Axis(0))
+            .map(|record| active
+                .iter()
+                .map(|&c| (c, metric.distance(record, self.centroids.row(c))))
+                .min_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+                .map(|(c, _)| c)
+                .unwrap_or(0)
+            )
+            .collect::<Vec<_>>();
+
+        Discrete::from_prediction(Array1::from_vec(nearest), self.k_max()).compacted()
+    }
+
+    // Inverse Davies-Bouldin index; partitions with fewer than two non-empty
+    // clusters score zero and singleton clusters are penalized
+    pub fn fitness(&self, data: &Data<F>) -> F {
+        self.fitness_with(data, &Euclidean)
+    }
+
+    pub fn fitness_with<M: Metric<F> + ?Sized>(&self, data: &Data<F>, metric: &M) -> F {
+        let prediction = self.to_discrete_with(data, metric);
+        let sizes = prediction.cluster_sizes();
+
+        if sizes.len() < 2 {
+            return F::zero();
+        }
+
+        let index = metric::davies_bouldin_with(data, &prediction, metric).unwrap_or(F::infinity());
+
+        let proper = sizes.iter().filter(|&&size| size > 1).count();
+        let penalty = F::cast(proper) / F::cast(sizes.len());
+
+        penalty / (index + F::cast(1e-12))
+    }
+}
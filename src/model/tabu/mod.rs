@@ -0,0 +1,115 @@
+use linfa::Float;
+use ndarray::Array2;
+use rand::{seq::index, Rng};
+
+use super::{history::History, partition::Partition, solution::Discrete};
+use crate::{error::ClusteringError, preprocessing::missing, utility::sampling::seeded_rng, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub max_iterations: usize,
+    pub tenure: usize,
+    pub n_candidates: Option<usize>,
+    pub seed: Option<u64>
+}
+
+// Best-improvement relabeling where returning a sample to a cluster it just
+// left is forbidden for `tenure` iterations, unless it yields a new best
+pub fn search<F: Float>(data: &Data<F>, initial: &Discrete, params: Parameters) -> Result<Discrete, ClusteringError> {
+    search_with(data, initial, params, &mut seeded_rng(params.seed), None)
+}
+
+fn search_with<F: Float, R: Rng>(
+    data: &Data<F>,
+    initial: &Discrete,
+    params: Parameters,
+    rng: &mut R,
+    mut history: Option<&mut History>
+) -> Result<Discrete, ClusteringError> {
+    let Parameters {
+        max_iterations,
+        tenure,
+        n_candidates,
+        ..
+    } = params;
+
+    let mut partition = Partition::new(data, initial)?;
+
+    let n_samples = partition.n_samples();
+    let n_classes = partition.n_classes();
+
+    let mut tabu_until = Array2::<usize>::zeros((n_samples, n_classes));
+
+    let mut best = partition.to_discrete();
+    let mut best_sse = partition.sse();
+
+    for time in 1..=max_iterations {
+        let samples = match n_candidates {
+            Some(n) => index::sample(rng, n_samples, n.min(n_samples)).into_vec(),
+            None => (0..n_samples).collect()
+        };
+
+        let mut chosen: Option<(usize, usize, F)> = None;
+
+        for &sample in samples.iter() {
+            for target in (0..n_classes).filter(|&c| c != partition.label(sample)) {
+                let delta = partition.delta(sample, target);
+
+                if !delta.is_finite() {
+                    continue;
+                }
+
+                let aspiration = partition.sse() + delta < best_sse;
+
+                if tabu_until[[sample, target]] >= time && !aspiration {
+                    continue;
+                }
+
+                if chosen.is_none_or(|(_, _, d)| delta < d) {
+                    chosen = Some((sample, target, delta));
+                }
+            }
+        }
+
+        let Some((sample, target, _)) = chosen else {
+            break;
+        };
+
+        tabu_until[[sample, partition.label(sample)]] = time + tenure;
+        partition.apply(sample, target);
+
+        if partition.sse() < best_sse {
+            best_sse = partition.sse();
+            best = partition.to_discrete();
+        }
+
+        if let Some(history) = history.as_deref_mut() {
+            history.record(&[-partition.sse()]);
+        }
+    }
+
+    Ok(best)
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Discrete, ClusteringError> {
+    run(data, params, None)
+}
+
+// Also returns the negated within-cluster sum of squares of the current
+// labeling after every iteration, so that higher is better as in the other histories
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Discrete, History), ClusteringError> {
+    let mut history = History::new();
+    let best = run(data, params, Some(&mut history))?;
+
+    Ok((best, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, history: Option<&mut History>) -> Result<Discrete, ClusteringError> {
+    missing::check(data)?;
+
+    let mut rng = seeded_rng(params.seed);
+    let initial = Partition::random(data, params.n_classes, &mut rng)?.to_discrete();
+
+    search_with(data, &initial, params, &mut rng, history)
+}
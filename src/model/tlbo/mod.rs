@@ -0,0 +1,163 @@
+use linfa::{Float, ParamGuard};
+use ndarray::Array2;
+use ndarray_rand::{rand_distr::Uniform, RandomExt};
+use rand::Rng;
+
+use super::{distance::Measure, history::History, initialization::Initialization, memetic::Refinement, runner::{self, Population, Settings}, solution::Fuzzy};
+use crate::{error::ClusteringError, utility::sampling::ExtendedRng, Data};
+
+// Rao et al. (2011): no algorithm-specific parameters beyond population size and iterations
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub n_classes: usize,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn builder(n_classes: usize) -> ParametersBuilder {
+        ParametersBuilder(Parameters {
+            n_classes,
+            n_agents: 20,
+            max_iterations: 500,
+            metric: Measure::Euclidean,
+            initialization: Initialization::Random,
+            refinement: Refinement::None,
+            seed: None
+        })
+    }
+
+    fn settings(&self) -> Settings {
+        Settings {
+            n_classes: self.n_classes,
+            n_agents: self.n_agents,
+            max_iterations: self.max_iterations,
+            metric: self.metric,
+            initialization: self.initialization,
+            refinement: self.refinement,
+            seed: self.seed
+        }
+    }
+
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        self.settings().validate(1)
+    }
+}
+
+// Unchecked parameters with defaults, turned into Parameters by ParamGuard::check
+#[derive(Debug, Clone, Copy)]
+pub struct ParametersBuilder(Parameters);
+
+impl ParametersBuilder {
+    pub fn n_agents(mut self, n_agents: usize) -> Self {
+        self.0.n_agents = n_agents;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.0.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn metric(mut self, metric: Measure) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    pub fn initialization(mut self, initialization: Initialization) -> Self {
+        self.0.initialization = initialization;
+        self
+    }
+
+    pub fn refinement(mut self, refinement: Refinement) -> Self {
+        self.0.refinement = refinement;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.0.seed = Some(seed);
+        self
+    }
+}
+
+impl ParamGuard for ParametersBuilder {
+    type Checked = Parameters;
+    type Error = ClusteringError;
+
+    fn check_ref(&self) -> Result<&Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(&self.0)
+    }
+
+    fn check(self) -> Result<Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+fn accept<F: Float>(learners: &mut Population<F>, i: usize, distribution: Array2<F>, data: &Data<F>, metric: Measure) {
+    let mut candidate = learners.agents[i].clone();
+    candidate.distribution = distribution;
+
+    let candidate_fitness = candidate.fitness_with(data, &metric);
+
+    if candidate_fitness > learners.fitness[i] {
+        learners.agents[i] = candidate;
+        learners.fitness[i] = candidate_fitness;
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    runner::with_history(|history| run(data, params, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    params.validate()?;
+
+    let Parameters { n_classes, n_agents, metric, .. } = params;
+    let shape = (data.records.nrows(), n_classes);
+
+    runner::run(data, params.settings(), history, |learners, _, rng| {
+        // Teacher phase: move the class mean towards the best learner
+        for i in 0..n_agents {
+            let teacher = learners.agents[learners.fittest()].distribution.clone();
+            let mean = learners.agents
+                .iter()
+                .fold(Array2::<F>::zeros(shape), |acc, learner| acc + &learner.distribution)
+                / F::cast(n_agents);
+
+            let teaching_factor = F::cast(rng.gen_range(1..=2));
+            let randomizer = Array2::random_using(shape, Uniform::new(F::zero(), F::one()), rng);
+
+            let distribution = &learners.agents[i].distribution + &(randomizer * (teacher - mean * teaching_factor));
+            accept(learners, i, distribution, data, metric);
+        }
+
+        // Learner phase: learn from a random classmate
+        if n_agents > 1 {
+            for i in 0..n_agents {
+                let j = rng.gen_range_excluding(0..n_agents, i);
+
+                let direction = match learners.fitness[i] > learners.fitness[j] {
+                    true => &learners.agents[i].distribution - &learners.agents[j].distribution,
+                    false => &learners.agents[j].distribution - &learners.agents[i].distribution
+                };
+
+                let randomizer = Array2::random_using(shape, Uniform::new(F::zero(), F::one()), rng);
+
+                let distribution = &learners.agents[i].distribution + &(randomizer * direction);
+                accept(learners, i, distribution, data, metric);
+            }
+        }
+
+        Ok(())
+    })
+}
@@ -0,0 +1,147 @@
+use std::{cmp::Ordering, f64::consts};
+
+use linfa::Float;
+use rand::Rng;
+
+use crate::{error::ClusteringError, model::{distance::Measure, history::History, solution::Activated}, preprocessing::missing, utility::sampling::{seeded_rng, ExtendedRng}, Data};
+
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub k_min: usize,
+    pub k_max: usize,
+    pub threshold: f64,
+    pub n_agents: usize,
+    pub max_iterations: usize,
+    pub metric: Measure,
+    pub spiral_constant: f64,
+    pub seed: Option<u64>
+}
+
+impl Parameters {
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        Activated::validate(self.k_min, self.k_max, self.threshold)?;
+
+        if self.n_agents < 2 {
+            return Err(ClusteringError::InvalidParameters(format!("WOA needs at least 2 agents, got {}", self.n_agents)));
+        }
+
+        if self.max_iterations == 0 {
+            return Err(ClusteringError::InvalidParameters("number of iterations must be positive".into()));
+        }
+
+        self.metric.validate()?;
+
+        if !self.spiral_constant.is_finite() {
+            return Err(ClusteringError::InvalidParameters(format!("spiral constant must be finite, got {}", self.spiral_constant)));
+        }
+
+        Ok(())
+    }
+}
+
+fn best_agent_index<F: Float>(agents: &[Activated<F>], data: &Data<F>, metric: Measure) -> usize {
+    agents
+        .iter()
+        .map(|agent| agent.fitness_with(data, &metric))
+        .enumerate()
+        .max_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal))
+        .map(|(i, _)| i)
+        .unwrap_or(0)
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Activated<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+// Also returns the fitness of the pod after every iteration
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Activated<F>, History), ClusteringError> {
+    let mut history = History::new();
+    let best = run(data, params, Some(&mut history))?;
+
+    Ok((best, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, mut history: Option<&mut History>) -> Result<Activated<F>, ClusteringError> {
+    params.validate()?;
+    missing::check_with(data, params.metric)?;
+
+    if params.k_min > data.records.nrows() {
+        return Err(ClusteringError::InvalidParameters(format!(
+            "k_min = {} exceeds the number of samples {}",
+            params.k_min,
+            data.records.nrows()
+        )));
+    }
+
+    let Parameters {
+        k_min,
+        k_max,
+        threshold,
+        n_agents,
+        max_iterations,
+        metric,
+        spiral_constant,
+        seed
+    } = params;
+
+    let mut rng = seeded_rng(seed);
+
+    let mut agents: Vec<Activated<F>> = (0..n_agents)
+        .map(|_| {
+            let mut agent = Activated::random_using(data, k_max, F::cast(threshold), &mut rng);
+            agent.repair(data, k_min, &mut rng);
+            agent
+        })
+        .collect();
+
+    for time in 0..max_iterations {
+        let decay_factor = 2.0 - 2.0 * time as f64 / max_iterations as f64;
+
+        let best_agent = agents[best_agent_index(&agents, data, metric)].clone();
+
+        for i in 0..n_agents {
+            let decay = decay_factor * rng.gen_range(-1.0..1.0);
+            let randomizer = rng.gen_range(0.0..2.0);
+
+            let mut agent = agents[i].clone();
+
+            if rng.gen_range(0.0..1.0) > 0.5 {
+                let target = match f64::abs(decay) < 1.0 {
+                    // Encircling prey
+                    true => &best_agent,
+                    // Exploration phase
+                    false => &agents[rng.gen_range_excluding(0..n_agents, i)]
+                };
+
+                let (randomizer, decay) = (F::cast(randomizer), F::cast(decay));
+
+                let displacement = &target.centroids * randomizer - &agent.centroids;
+                agent.centroids = &target.centroids - &(displacement * decay);
+
+                let displacement = &target.activations * randomizer - &agent.activations;
+                agent.activations = &target.activations - &(displacement * decay);
+            } else {
+                // Exploitation phase
+                let spiral_displacement = rng.gen_range(-1.0..1.0);
+                let factor = F::cast((spiral_constant * spiral_displacement).exp()
+                    * (2.0 * consts::PI * spiral_displacement).cos());
+
+                let displacement = (&best_agent.centroids - &agent.centroids).mapv_into(|x| x.abs());
+                agent.centroids = displacement * factor + &best_agent.centroids;
+
+                let displacement = (&best_agent.activations - &agent.activations).mapv_into(|x| x.abs());
+                agent.activations = displacement * factor + &best_agent.activations;
+            }
+
+            agent.repair(data, k_min, &mut rng);
+            agents[i] = agent;
+        }
+
+        if let Some(history) = history.as_deref_mut() {
+            history.record(&agents.iter().map(|agent| agent.fitness_with(data, &metric)).collect::<Vec<_>>());
+        }
+    }
+
+    let best_agent_index = best_agent_index(&agents, data, metric);
+    Ok(agents[best_agent_index].clone())
+}
@@ -1,14 +1,16 @@
+pub mod dynamic;
 
-use super::solution::Fuzzy;
-use crate::utility::sampling::ExtendedRng;
-use crate::Data;
+use super::{distance::Measure, history::History, initialization::{self, Initialization}, memetic::Refinement, mutation::Mutation, solution::Fuzzy};
+use crate::utility::sampling::{seeded_rng, ExtendedRng};
+use crate::{error::ClusteringError, Data};
+use linfa::{Float, ParamGuard};
 use rand::distributions::Distribution;
 use rand::Rng;
 
 use ndarray::Array2;
 use ndarray_rand::rand_distr::Uniform;
 use ndarray_rand::RandomExt;
-use std::{f64::consts, error::Error};
+use std::f64::consts;
 
 #[derive(Debug, Clone, Copy)]
 pub struct Parameters {
@@ -16,14 +18,127 @@ pub struct Parameters {
     pub n_agents: usize,
     pub max_iterations: usize,
     pub spiral_constant: f64,
-    pub n_spiral_samples: usize
+    pub n_spiral_samples: usize,
+    pub metric: Measure,
+    pub initialization: Initialization,
+    pub refinement: Refinement,
+    pub mutation: Mutation,
+    pub seed: Option<u64>
 }
 
-fn best_agent_index(agents: &Vec<Fuzzy>, data: &Data) -> usize {
+impl Parameters {
+    pub fn builder(n_classes: usize) -> ParametersBuilder {
+        ParametersBuilder(Parameters {
+            n_classes,
+            n_agents: 50,
+            max_iterations: 2000,
+            spiral_constant: 1.0,
+            n_spiral_samples: 50,
+            metric: Measure::Euclidean,
+            initialization: Initialization::Random,
+            refinement: Refinement::None,
+            mutation: Mutation::None,
+            seed: None
+        })
+    }
+
+    // Checks that do not depend on the data; the number of classes is compared
+    // with the number of samples when the population is initialized
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        if self.n_classes == 0 {
+            return Err(ClusteringError::InvalidParameters("number of classes must be positive".into()));
+        }
+
+        // Exploration moves towards a whale other than the current one
+        if self.n_agents < 2 {
+            return Err(ClusteringError::InvalidParameters(format!("WOA needs at least 2 agents, got {}", self.n_agents)));
+        }
+
+        if self.max_iterations == 0 {
+            return Err(ClusteringError::InvalidParameters("number of iterations must be positive".into()));
+        }
+
+        if !self.spiral_constant.is_finite() {
+            return Err(ClusteringError::InvalidParameters(format!("spiral constant must be finite, got {}", self.spiral_constant)));
+        }
+
+        self.metric.validate()?;
+        self.refinement.validate(self.metric)?;
+        self.mutation.validate()
+    }
+}
+
+// Unchecked parameters with defaults, turned into Parameters by ParamGuard::check
+#[derive(Debug, Clone, Copy)]
+pub struct ParametersBuilder(Parameters);
+
+impl ParametersBuilder {
+    pub fn n_agents(mut self, n_agents: usize) -> Self {
+        self.0.n_agents = n_agents;
+        self
+    }
+
+    pub fn max_iterations(mut self, max_iterations: usize) -> Self {
+        self.0.max_iterations = max_iterations;
+        self
+    }
+
+    pub fn spiral_constant(mut self, spiral_constant: f64) -> Self {
+        self.0.spiral_constant = spiral_constant;
+        self
+    }
+
+    pub fn n_spiral_samples(mut self, n_spiral_samples: usize) -> Self {
+        self.0.n_spiral_samples = n_spiral_samples;
+        self
+    }
+
+    pub fn metric(mut self, metric: Measure) -> Self {
+        self.0.metric = metric;
+        self
+    }
+
+    pub fn initialization(mut self, initialization: Initialization) -> Self {
+        self.0.initialization = initialization;
+        self
+    }
+
+    pub fn refinement(mut self, refinement: Refinement) -> Self {
+        self.0.refinement = refinement;
+        self
+    }
+
+    pub fn mutation(mut self, mutation: Mutation) -> Self {
+        self.0.mutation = mutation;
+        self
+    }
+
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.0.seed = Some(seed);
+        self
+    }
+}
+
+impl ParamGuard for ParametersBuilder {
+    type Checked = Parameters;
+    type Error = ClusteringError;
+
+    fn check_ref(&self) -> Result<&Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(&self.0)
+    }
+
+    fn check(self) -> Result<Parameters, ClusteringError> {
+        self.0.validate()?;
+        Ok(self.0)
+    }
+}
+
+fn best_agent_index<F: Float>(agents: &[Fuzzy<F>], data: &Data<F>, metric: Measure) -> usize {
     let mut best_index = 0;
-    let mut best_fitness = f64::NEG_INFINITY;
+    let mut best_fitness = F::neg_infinity();
     for (i, agent) in agents.iter().enumerate() {
-        let fitness = agent.fitness(data);
+        let fitness = agent.fitness_with(data, &metric);
         best_index = if fitness > best_fitness {
             best_fitness = fitness;
             i
@@ -39,93 +154,132 @@ const fn unravel_2d_index(ind: usize, n_cols: usize) -> (usize, usize) {
     (ind / n_cols, ind % n_cols)
 }
 
-pub fn fit(data: &Data, params: Parameters) -> Result<Fuzzy, Box<dyn Error>> {
+pub fn init<F: Float, R: Rng>(data: &Data<F>, params: &Parameters, rng: &mut R) -> Result<Vec<Fuzzy<F>>, ClusteringError> {
+    params.validate()?;
+
+    initialization::population(data, params.n_agents, params.n_classes, params.initialization, params.metric, rng)
+}
+
+pub fn step<F: Float, R: Rng>(data: &Data<F>, params: &Parameters, agents: &mut [Fuzzy<F>], time: usize, rng: &mut R) {
     let n_samples = data.records.nrows();
-    
+
     let Parameters {
         n_classes,
         n_agents,
         max_iterations,
         spiral_constant,
-        n_spiral_samples
-    } = params;
+        n_spiral_samples,
+        metric,
+        refinement,
+        mutation,
+        ..
+    } = *params;
 
     let n_dimensions = n_samples * n_classes;
 
-    let mut agents: Vec<Fuzzy> = (0..n_agents)
-        .map(|_| Fuzzy::random(n_samples, params.n_classes))
-        .collect();
-
-    let mut rng = rand::thread_rng();
-
-    for time in 0..max_iterations {
-        let decay_factor = 2.0 - 2.0 * time as f64 / max_iterations as f64;
-        let decay = decay_factor * Array2::random(
-            (n_samples, n_classes),
-            Uniform::new(-1.0, 1.0)
-        );
-
-        let randomizer = Array2::random(
-            (n_samples, n_classes),
-            Uniform::new(0.0, 2.0)
-        );
-
-        let agents_direct = &agents as *const Vec<Fuzzy>;
-
-        let best_agent_index = best_agent_index(&agents, data);
-        let best_agent = unsafe { &(*agents_direct)[best_agent_index] }; // Allows data race
-        // let best_agent = agents[best_agent_index].clone(); // Prevents data race
-
-        for (i, agent) in agents.iter_mut().enumerate() {
-            if rng.gen_range(0.0..1.0) > 0.5 {
-                if decay_factor < 1.0 {
-                    // Encircling prey
-                    let displacement = &randomizer * &best_agent.distribution - &agent.distribution;
-                    agent.distribution = &best_agent.distribution - &decay * &displacement;
-                } else {
-                    // Exploration phase
-                    let rand_agent_index = rng.gen_range_excluding(0..n_agents, i);
-                    let rand_agent = unsafe { &(*agents_direct)[rand_agent_index] };
-                    let displacement = &randomizer * &rand_agent.distribution - &agent.distribution;
-                    agent.distribution = &rand_agent.distribution - &decay * &displacement;
-                }
+    let decay_factor = 2.0 - 2.0 * time as f64 / max_iterations as f64;
+    let decay = Array2::random_using(
+        (n_samples, n_classes),
+        Uniform::new(-F::one(), F::one()),
+        rng
+    ) * F::cast(decay_factor);
+
+    let randomizer = Array2::random_using(
+        (n_samples, n_classes),
+        Uniform::new(F::zero(), F::cast(2.0)),
+        rng
+    );
+
+    // Every whale moves relative to the positions at the start of the iteration
+    let previous = agents.to_vec();
+
+    let best_agent_index = best_agent_index(agents, data, metric);
+    let best_agent = &previous[best_agent_index];
+
+    for (i, agent) in agents.iter_mut().enumerate() {
+        if rng.gen_range(0.0..1.0) > 0.5 {
+            if decay_factor < 1.0 {
+                // Encircling prey
+                let displacement = &randomizer * &best_agent.distribution - &agent.distribution;
+                agent.distribution = &best_agent.distribution - &decay * &displacement;
             } else {
-                for _ in 0..n_spiral_samples {
-                    // Exploitation phase
-                    let spiral_displacement = Uniform::new(0.0, 1.0).sample(&mut rng);
-                    let spiral_phase = 2.0 * consts::PI * spiral_displacement;
-
-                    let (dim1, dim2) = rng.gen_distinct_pair_range(0..n_dimensions);
-
-                    let x_index = unravel_2d_index(dim1, n_classes);
-                    let y_index = unravel_2d_index(dim2, n_classes);
-
-                    let exp_factor = (spiral_constant * spiral_displacement).exp();
-
-                    unsafe {
-                        let best_x = best_agent.distribution.uget(x_index);
-                        let x = agent.distribution.uget_mut(x_index);
-                        let x_displacement = best_x - *x;
-
-                        *x = x_displacement 
-                            * exp_factor
-                            * spiral_phase.cos()
-                            + best_x;
-
-                        let best_y = best_agent.distribution.uget(y_index);
-                        let y = agent.distribution.uget_mut(y_index);
-                        let y_displacement = best_y - *y;
-
-                        *y = y_displacement 
-                            * exp_factor
-                            * spiral_phase.sin()
-                            + best_y;
-                    }
-                }
+                // Exploration phase
+                let rand_agent_index = rng.gen_range_excluding(0..n_agents, i);
+                let rand_agent = &previous[rand_agent_index];
+                let displacement = &randomizer * &rand_agent.distribution - &agent.distribution;
+                agent.distribution = &rand_agent.distribution - &decay * &displacement;
             }
+        } else {
+            for _ in 0..n_spiral_samples {
+                // Exploitation phase
+                let spiral_displacement = Uniform::new(0.0, 1.0).sample(rng);
+                let spiral_phase = 2.0 * consts::PI * spiral_displacement;
+
+                let (dim1, dim2) = rng.gen_distinct_pair_range(0..n_dimensions);
+
+                let x_index = unravel_2d_index(dim1, n_classes);
+                let y_index = unravel_2d_index(dim2, n_classes);
+
+                let exp_factor = (spiral_constant * spiral_displacement).exp();
+
+                let best_x = best_agent.distribution[x_index];
+                let x = &mut agent.distribution[x_index];
+                let x_displacement = best_x - *x;
+
+                *x = x_displacement 
+                    * F::cast(exp_factor * spiral_phase.cos())
+                    + best_x;
+
+                let best_y = best_agent.distribution[y_index];
+                let y = &mut agent.distribution[y_index];
+                let y_displacement = best_y - *y;
+
+                *y = y_displacement 
+                    * F::cast(exp_factor * spiral_phase.sin())
+                    + best_y;
+            }
+        }
+
+        if i != best_agent_index {
+            mutation.apply(agent, &best_agent.distribution, rng);
+        }
+    }
+
+    if refinement.is_due(time) {
+        let mut fitness = agents
+            .iter()
+            .map(|agent| agent.fitness_with(data, &metric))
+            .collect::<Vec<_>>();
+
+        refinement.refine_best(data, agents, &mut fitness, &metric);
+    }
+}
+
+pub fn fit<F: Float>(data: &Data<F>, params: Parameters) -> Result<Fuzzy<F>, ClusteringError> {
+    run(data, params, None)
+}
+
+// Also returns the fitness of the pod after every iteration
+pub fn fit_with_history<F: Float>(data: &Data<F>, params: Parameters) -> Result<(Fuzzy<F>, History), ClusteringError> {
+    let mut history = History::new();
+    let best = run(data, params, Some(&mut history))?;
+
+    Ok((best, history))
+}
+
+fn run<F: Float>(data: &Data<F>, params: Parameters, mut history: Option<&mut History>) -> Result<Fuzzy<F>, ClusteringError> {
+    let mut rng = seeded_rng(params.seed);
+
+    let mut agents = init(data, &params, &mut rng)?;
+
+    for time in 0..params.max_iterations {
+        step(data, &params, &mut agents, time, &mut rng);
+
+        if let Some(history) = history.as_deref_mut() {
+            history.record(&agents.iter().map(|agent| agent.fitness_with(data, &params.metric)).collect::<Vec<_>>());
         }
     }
 
-    let best_agent_index = best_agent_index(&agents, data);
-    Ok(agents[best_agent_index].clone())
+    let best_agent_index = best_agent_index(&agents, data, params.metric);
+    Ok(params.refinement.finalize(data, agents[best_agent_index].clone(), &params.metric))
 }
@@ -1,19 +1,33 @@
-use std::error::Error;
 use itertools::Itertools;
 use ndarray::{self, s, Ix1};
 
 use plotly::{
-    color::NamedColor, 
+    color::{Color, NamedColor},
     common::{Font, Marker, Mode, Title},
     layout::{Axis, Legend},
     Layout, Plot, Scatter
 };
 
-use crate::{model::solution::Discrete, Data};
+use super::palette::Classes;
+use crate::{error::ClusteringError, model::solution::Discrete, Data};
 
 
-pub fn plot(data: Data, prediction: Discrete, x: usize, y: usize, title: &str) -> Result<Plot, Box<dyn Error>> {
+// Clusters drawn with a generated palette and numbered names
+pub fn plot(data: Data, prediction: Discrete, x: usize, y: usize, title: &str) -> Result<Plot, ClusteringError> {
+    let classes = Classes::generated(n_labels(&prediction));
+    plot_with(data, prediction, x, y, title, &classes)
+}
+
+pub fn plot_with<C: Color + Clone>(
+    data: Data,
+    prediction: Discrete,
+    x: usize,
+    y: usize,
+    title: &str,
+    classes: &Classes<C>
+) -> Result<Plot, ClusteringError> {
     let names = data.feature_names();
+    super::check_features(&data, &names, x, y)?;
 
     let x_name = &names[x];
     let y_name = &names[y];
@@ -29,18 +43,6 @@ pub fn plot(data: Data, prediction: Discrete, x: usize, y: usize, title: &str) -
         .unique()
         .collect_vec();
 
-    let colors = vec![
-        NamedColor::Aquamarine,
-        NamedColor::Aqua,
-        NamedColor::CornflowerBlue
-    ];
-
-    let labels = vec![
-        "Setosa",
-        "Versicolor",
-        "Virginica"
-    ];
-
     let x = records
         .slice(s![.., x])
         .into_dimensionality::<Ix1>()?
@@ -61,7 +63,7 @@ pub fn plot(data: Data, prediction: Discrete, x: usize, y: usize, title: &str) -
         .y_axis(Axis::new().anchor("y").title(Title::new(y_name)))
         .legend(
             Legend::new()
-                .title("Species".into())
+                .title(classes.title.as_str().into())
                 .border_color(NamedColor::Gray)
                 .border_width(1)
         );
@@ -69,6 +71,8 @@ pub fn plot(data: Data, prediction: Discrete, x: usize, y: usize, title: &str) -
     plot.set_layout(layout);
 
     for group in groups {
+        let (color, name) = classes.style(group)?;
+
         let x = x
             .iter()
             .cloned()
@@ -89,13 +93,35 @@ pub fn plot(data: Data, prediction: Discrete, x: usize, y: usize, title: &str) -
             .mode(Mode::Markers)
             .marker(
                 Marker::new()
-                    .color(colors[group])
+                    .color(color)
                     .size(7)
             )
-            .name(labels[group]);
+            .name(&name);
 
         plot.add_trace(trace);
     }
 
     Ok(plot)
-}
\ No newline at end of file
+}
+
+// Same plot on the first two principal components instead of two raw features
+pub fn plot_principal(data: Data, prediction: Discrete, title: &str) -> Result<Plot, ClusteringError> {
+    plot(super::principal_plane(&data)?, prediction, 0, 1, title)
+}
+
+pub fn plot_principal_with<C: Color + Clone>(
+    data: Data,
+    prediction: Discrete,
+    title: &str,
+    classes: &Classes<C>
+) -> Result<Plot, ClusteringError> {
+    plot_with(super::principal_plane(&data)?, prediction, 0, 1, title, classes)
+}
+
+// Enough classes for every label, even if n_classes undercounts them
+fn n_labels(prediction: &Discrete) -> usize {
+    prediction.indicators
+        .iter()
+        .map(|&label| label + 1)
+        .fold(prediction.n_classes, usize::max)
+}
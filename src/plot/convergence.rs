@@ -0,0 +1,119 @@
+use plotly::{
+    color::{NamedColor, Rgb, Rgba},
+    common::{Fill, Font, Line, Mode, Title},
+    layout::{Axis, Legend},
+    Layout, Plot, Scatter
+};
+
+use super::palette;
+use crate::{error::ClusteringError, model::history::History, utility::order::quantile};
+
+
+fn layout(title: &str) -> Layout {
+    Layout::new()
+        .title(Title::new(title).font(Font::new().size(25)))
+        .x_axis(Axis::new().anchor("x").title(Title::new("Iteration")))
+        .y_axis(Axis::new().anchor("y").title(Title::new("Fitness")))
+        .legend(
+            Legend::new()
+                .border_color(NamedColor::Gray)
+                .border_width(1)
+        )
+}
+
+// Best so far and the best, mean and worst fitness of the population over a single run
+pub fn plot(history: &History, title: &str) -> Result<Plot, ClusteringError> {
+    if history.is_empty() {
+        return Err(ClusteringError::Plotting("history has no iterations".into()));
+    }
+
+    let iterations = (1..=history.len()).collect::<Vec<_>>();
+    let curves = [
+        ("Best so far", &history.best_so_far),
+        ("Best", &history.best),
+        ("Mean", &history.mean),
+        ("Worst", &history.worst)
+    ];
+
+    let mut plot = Plot::new();
+    plot.set_layout(layout(title));
+
+    for ((name, values), color) in curves.into_iter().zip(palette::generate(curves.len())) {
+        let trace = Scatter::new(iterations.clone(), values.clone())
+            .mode(Mode::Lines)
+            .line(Line::new().color(color).width(2.0))
+            .name(name);
+
+        plot.add_trace(trace);
+    }
+
+    Ok(plot)
+}
+
+// Median of the best fitness so far over repeated runs with the interquartile range
+// shaded, one curve per algorithm; runs may stop after different numbers of iterations
+pub fn plot_runs(algorithms: &[(&str, Vec<History>)], title: &str) -> Result<Plot, ClusteringError> {
+    let mut plot = Plot::new();
+    plot.set_layout(layout(title));
+
+    for ((name, runs), (r, g, b)) in algorithms.iter().zip(palette::channels(algorithms.len())) {
+        let n_iterations = runs.iter().map(History::len).max().unwrap_or(0);
+
+        if n_iterations == 0 {
+            return Err(ClusteringError::Plotting(format!("no recorded iterations for {}", name)));
+        }
+
+        let mut iterations = Vec::with_capacity(n_iterations);
+        let mut lower = Vec::with_capacity(n_iterations);
+        let mut median = Vec::with_capacity(n_iterations);
+        let mut upper = Vec::with_capacity(n_iterations);
+
+        for time in 0..n_iterations {
+            let mut values = runs
+                .iter()
+                .filter_map(|run| run.best_so_far.get(time).cloned())
+                .filter(|x| !x.is_nan())
+                .collect::<Vec<_>>();
+
+            if values.is_empty() {
+                continue;
+            }
+
+            values.sort_by(f64::total_cmp);
+
+            iterations.push(time + 1);
+            lower.push(quantile(&values, 0.25));
+            median.push(quantile(&values, 0.5));
+            upper.push(quantile(&values, 0.75));
+        }
+
+        // The band is drawn by filling from the upper quartile down to the lower one
+        let lower = Scatter::new(iterations.clone(), lower)
+            .mode(Mode::Lines)
+            .line(Line::new().color(Rgba::new(r, g, b, 0.0)).width(0.0))
+            .legend_group(name)
+            .show_legend(false)
+            .name(format!("{} Q1", name));
+
+        let upper = Scatter::new(iterations.clone(), upper)
+            .mode(Mode::Lines)
+            .line(Line::new().color(Rgba::new(r, g, b, 0.0)).width(0.0))
+            .fill(Fill::ToNextY)
+            .fill_color(Rgba::new(r, g, b, 0.2))
+            .legend_group(name)
+            .show_legend(false)
+            .name(format!("{} Q3", name));
+
+        let median = Scatter::new(iterations, median)
+            .mode(Mode::Lines)
+            .line(Line::new().color(Rgb::new(r, g, b)).width(2.0))
+            .legend_group(name)
+            .name(name);
+
+        plot.add_trace(lower);
+        plot.add_trace(upper);
+        plot.add_trace(median);
+    }
+
+    Ok(plot)
+}
@@ -1,3 +1,37 @@
 pub mod cluster_map;
 pub mod scatter_matrix;
 pub mod prediction_map;
+pub mod pareto_front;
+pub mod palette;
+pub mod convergence;
+
+
+use crate::{error::ClusteringError, preprocessing::reduction::Pca, Data};
+
+// Records projected onto their first two principal components, with the
+// explained variance in the feature names used as axis titles
+pub(crate) fn principal_plane(data: &Data) -> Result<Data, ClusteringError> {
+    let pca = Pca::fit(data, 2)?;
+
+    let names = pca.explained_variance_ratio
+        .iter()
+        .enumerate()
+        .map(|(i, ratio)| format!("PC{} ({:.1}%)", i + 1, 100.0 * ratio))
+        .collect::<Vec<_>>();
+
+    Ok(pca.transform(data)?.with_feature_names(names))
+}
+
+// Both plotted features must be columns of the records with a name for the axis title
+pub(crate) fn check_features(data: &Data, names: &[String], x: usize, y: usize) -> Result<(), ClusteringError> {
+    let n_features = data.records.ncols().min(names.len());
+
+    match [x, y].into_iter().find(|&index| index >= n_features) {
+        Some(index) => Err(ClusteringError::InvalidParameters(format!(
+            "feature index {} out of range for {} features",
+            index,
+            n_features
+        ))),
+        None => Ok(())
+    }
+}
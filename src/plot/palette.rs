@@ -0,0 +1,102 @@
+use plotly::color::{Color, NamedColor, Rgb};
+
+use crate::{error::ClusteringError, IRIS_COLORS, IRIS_LABELS};
+
+// k colors with evenly spaced hues; past ten classes neighbouring hues get
+// alternating lightness so that they stay distinguishable
+pub fn generate(k: usize) -> Vec<Rgb> {
+    channels(k)
+        .into_iter()
+        .map(|(r, g, b)| Rgb::new(r, g, b))
+        .collect()
+}
+
+// Red, green and blue channels of the generated colors, e.g. to build translucent variants
+pub fn channels(k: usize) -> Vec<(u8, u8, u8)> {
+    (0..k)
+        .map(|i| {
+            let hue = (200.0 + 360.0 * i as f64 / k as f64) % 360.0;
+            let lightness = match k > 10 && i % 2 == 1 {
+                true => 0.38,
+                false => 0.55
+            };
+
+            hsl_to_rgb(hue, 0.7, lightness)
+        })
+        .collect()
+}
+
+fn hsl_to_rgb(hue: f64, saturation: f64, lightness: f64) -> (u8, u8, u8) {
+    let chroma = (1.0 - (2.0 * lightness - 1.0).abs()) * saturation;
+    let sector = hue / 60.0;
+    let second = chroma * (1.0 - (sector % 2.0 - 1.0).abs());
+
+    let (r, g, b) = match sector as usize {
+        0 => (chroma, second, 0.0),
+        1 => (second, chroma, 0.0),
+        2 => (0.0, chroma, second),
+        3 => (0.0, second, chroma),
+        4 => (second, 0.0, chroma),
+        _ => (chroma, 0.0, second)
+    };
+
+    let offset = lightness - chroma / 2.0;
+    let channel = |value: f64| (255.0 * (value + offset)).round() as u8;
+
+    (channel(r), channel(g), channel(b))
+}
+
+// Legend title, names and colors of the classes shown in a plot
+#[derive(Debug, Clone)]
+pub struct Classes<C = Rgb> {
+    pub title: String,
+    pub names: Vec<String>,
+    pub colors: Vec<C>
+}
+
+impl Classes<Rgb> {
+    // "Cluster 1", "Cluster 2", ... with a generated palette
+    pub fn generated(k: usize) -> Self {
+        Classes {
+            title: "Cluster".into(),
+            names: (1..=k).map(|i| format!("Cluster {}", i)).collect(),
+            colors: generate(k)
+        }
+    }
+}
+
+impl Classes<NamedColor> {
+    pub fn iris() -> Self {
+        Classes {
+            title: "Species".into(),
+            names: IRIS_LABELS.iter().map(|name| name.to_string()).collect(),
+            colors: IRIS_COLORS.to_vec()
+        }
+    }
+}
+
+impl<C: Color + Clone> Classes<C> {
+    pub fn new(title: &str, names: Vec<String>, colors: Vec<C>) -> Self {
+        Classes { title: title.into(), names, colors }
+    }
+
+    pub fn with_title(self, title: &str) -> Self {
+        Classes { title: title.into(), ..self }
+    }
+
+    // Fails for labels without a color; missing names fall back to the cluster number
+    pub(crate) fn style(&self, class: usize) -> Result<(C, String), ClusteringError> {
+        let color = self.colors.get(class).cloned().ok_or_else(|| ClusteringError::Plotting(format!(
+            "no color for class {}, only {} colors were given",
+            class,
+            self.colors.len()
+        )))?;
+
+        let name = self.names
+            .get(class)
+            .cloned()
+            .unwrap_or_else(|| format!("Cluster {}", class + 1));
+
+        Ok((color, name))
+    }
+}
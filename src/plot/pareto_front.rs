@@ -0,0 +1,73 @@
+use plotly::{
+    color::NamedColor,
+    common::{Font, Marker, MarkerSymbol, Mode, Title},
+    layout::{Axis, Legend},
+    Layout, Plot, Scatter
+};
+
+use crate::{error::ClusteringError, model::multiobjective::Front};
+
+
+pub fn plot(front: &Front, x: usize, y: usize, title: &str) -> Result<Plot, ClusteringError> {
+    let n_objectives = front.objectives.len();
+
+    if x >= n_objectives || y >= n_objectives {
+        return Err(ClusteringError::InvalidParameters(format!("front has only {} objectives", n_objectives)));
+    }
+
+    let x_name = front.objectives[x].name();
+    let y_name = front.objectives[y].name();
+
+    let mut order = (0..front.scores.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| front.scores[a][x].total_cmp(&front.scores[b][x]));
+
+    let x_values = order.iter().map(|&i| front.scores[i][x]).collect::<Vec<_>>();
+    let y_values = order.iter().map(|&i| front.scores[i][y]).collect::<Vec<_>>();
+
+    let labels = order
+        .iter()
+        .map(|&i| format!("k = {}", front.labelings[i].n_classes))
+        .collect::<Vec<_>>();
+
+    let mut plot = Plot::new();
+
+    let layout = Layout::new()
+        .title(Title::new(title).font(Font::new().size(25)))
+        .x_axis(Axis::new().anchor("x").title(Title::new(x_name)))
+        .y_axis(Axis::new().anchor("y").title(Title::new(y_name)))
+        .legend(
+            Legend::new()
+                .border_color(NamedColor::Gray)
+                .border_width(1)
+        );
+
+    plot.set_layout(layout);
+
+    let trace = Scatter::new(x_values, y_values)
+        .mode(Mode::LinesMarkers)
+        .marker(
+            Marker::new()
+                .color(NamedColor::CornflowerBlue)
+                .size(7)
+        )
+        .text_array(labels)
+        .name("Pareto front");
+
+    plot.add_trace(trace);
+
+    if let Some(knee) = front.knee() {
+        let trace = Scatter::new(vec![front.scores[knee][x]], vec![front.scores[knee][y]])
+            .mode(Mode::Markers)
+            .marker(
+                Marker::new()
+                    .color(NamedColor::Crimson)
+                    .symbol(MarkerSymbol::Star)
+                    .size(14)
+            )
+            .name("Knee");
+
+        plot.add_trace(trace);
+    }
+
+    Ok(plot)
+}
@@ -1,4 +1,3 @@
-use std::error::Error;
 use ndarray::{s, Ix1};
 use plotly::{
     color::NamedColor,
@@ -7,7 +6,7 @@ use plotly::{
     Layout, Plot, Scatter
 };
 
-use crate::{model::solution::Discrete, Data, INDICATOR_COLORS, INDICATOR_LABELS};
+use crate::{error::ClusteringError, model::solution::Discrete, Data, INDICATOR_COLORS, INDICATOR_LABELS};
 
 
 pub fn plot(
@@ -16,8 +15,9 @@ pub fn plot(
     x: usize,
     y: usize,
     title: &str
-) -> Result<Plot, Box<dyn Error>> {
+) -> Result<Plot, ClusteringError> {
     let names = data.feature_names();
+    super::check_features(&data, &names, x, y)?;
 
     let x_name = &names[x];
     let y_name = &names[y];
@@ -95,4 +95,9 @@ pub fn plot(
     plot.add_trace(non_matching);
     
     Ok(plot)
-}
\ No newline at end of file
+}
+
+// Same plot on the first two principal components instead of two raw features
+pub fn plot_principal(data: Data, prediction: Discrete, title: &str) -> Result<Plot, ClusteringError> {
+    plot(super::principal_plane(&data)?, prediction, 0, 1, title)
+}
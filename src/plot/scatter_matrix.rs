@@ -1,28 +1,34 @@
-use std::error::Error;
 use ndarray::{self, s, Ix1};
 use linfa::dataset::Records;
 
 use plotly::{
-    color::NamedColor, common::{Font, Line, Marker, Mode, Title}, layout::{Annotation, GridPattern, LayoutGrid, Legend}, Histogram, Layout, Plot, Scatter
+    color::{Color, NamedColor}, common::{Font, Line, Marker, Mode, Title}, layout::{Annotation, GridPattern, LayoutGrid, Legend}, Histogram, Layout, Plot, Scatter
 };
 
-use crate::Data;
+use super::palette::Classes;
+use crate::{error::ClusteringError, Data};
 
 
-pub fn plot(data: Data, title: &str) -> Result<Plot, Box<dyn Error>> {
+// Points colored by target with a generated palette
+pub fn plot(data: Data, title: &str) -> Result<Plot, ClusteringError> {
+    let n_classes = data.targets().iter().max().map_or(0, |&label| label + 1);
+    plot_with(data, title, &Classes::generated(n_classes))
+}
+
+pub fn plot_with<C: Color + Clone>(data: Data, title: &str, classes: &Classes<C>) -> Result<Plot, ClusteringError> {
     let n = data.nfeatures();
     let names = data.feature_names();
 
     let records = data.records();
     let target = data.targets().to_vec();
 
+    let colors = target
+        .iter()
+        .map(|&class| classes.style(class).map(|(color, _)| color))
+        .collect::<Result<Vec<_>, _>>()?;
+
     let scatter_marker = Marker::new()
-        .color_array(target.iter().map(|i| match i {
-            0 => NamedColor::Aquamarine,
-            1 => NamedColor::Aqua,
-            2 => NamedColor::CornflowerBlue,
-            _ => panic!()
-        }).collect())
+        .color_array(colors)
         .size(5);
 
     let mut plot = Plot::new();
@@ -37,7 +43,7 @@ pub fn plot(data: Data, title: &str) -> Result<Plot, Box<dyn Error>> {
         )
         .legend(
             Legend::new()
-                .title("Species".into())
+                .title(classes.title.as_str().into())
                 .border_width(1)
                 .border_color(NamedColor::Black)
         );
@@ -0,0 +1,197 @@
+use std::cmp::Ordering;
+
+use linfa::Float;
+use ndarray::{Array2, Axis};
+
+use crate::{error::ClusteringError, model::distance::{Measure, Metric, NanEuclidean}, Data};
+
+// How NaN entries of the records are dealt with before clustering
+#[derive(Debug, Clone, Copy)]
+pub enum Policy {
+    DropRows,
+    Mean,
+    Median,
+    // Average of the feature over the nearest rows that have it, with
+    // distances computed on the coordinates present in both rows
+    Knn { n_neighbors: usize },
+    // Keeps the missing entries; the data must then be clustered with
+    // Measure::NanEuclidean, which skips them in every distance
+    NanAware
+}
+
+pub fn count<F: Float>(data: &Data<F>) -> usize {
+    data.records.iter().filter(|x| x.is_nan()).count()
+}
+
+// Fails with a description of the missing entries, algorithms call it before fitting
+pub fn check<F: Float>(data: &Data<F>) -> Result<(), ClusteringError> {
+    let missing = data.records
+        .axis_iter(Axis(1))
+        .zip(data.feature_names())
+        .map(|(column, name)| (name, column.iter().filter(|x| x.is_nan()).count()))
+        .filter(|(_, count)| *count > 0)
+        .map(|(name, count)| format!("{} ({})", name, count))
+        .collect::<Vec<_>>();
+
+    match missing.is_empty() {
+        true => Ok(()),
+        false => Err(ClusteringError::NanEncountered(format!(
+            "records contain missing values in features {}; choose a policy with preprocessing::missing::apply, or cluster with Measure::NanEuclidean",
+            missing.join(", ")
+        )))
+    }
+}
+
+// Like check, but lets missing values through to metrics that skip them
+pub fn check_with<F: Float>(data: &Data<F>, metric: Measure) -> Result<(), ClusteringError> {
+    match metric.accepts_missing() {
+        true => Ok(()),
+        false => check(data)
+    }
+}
+
+pub fn apply<F: Float>(data: &Data<F>, policy: Policy) -> Result<Data<F>, ClusteringError> {
+    let records = &data.records;
+
+    let imputed = match policy {
+        Policy::DropRows => {
+            let complete = (0..records.nrows())
+                .filter(|&i| records.row(i).iter().all(|x| !x.is_nan()))
+                .collect::<Vec<_>>();
+
+            if complete.is_empty() {
+                return Err(ClusteringError::InvalidParameters("every row has a missing value, nothing left after dropping".into()));
+            }
+
+            let targets = match data.targets.len() == records.nrows() {
+                true => data.targets.select(Axis(0), &complete),
+                false => data.targets.clone()
+            };
+
+            return Ok(data
+                .clone()
+                .with_records(records.select(Axis(0), &complete))
+                .with_targets(targets)
+                .with_feature_names(data.feature_names())
+            );
+        },
+        Policy::Mean => impute_columns(records, |present| present.iter().fold(F::zero(), |acc, &x| acc + x) / F::cast(present.len()))?,
+        Policy::Median => impute_columns(records, |present| {
+            present.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+            let middle = present.len() / 2;
+            match present.len() % 2 {
+                0 => (present[middle - 1] + present[middle]) / F::cast(2.0),
+                _ => present[middle]
+            }
+        })?,
+        Policy::Knn { n_neighbors } => impute_knn(records, n_neighbors)?,
+        Policy::NanAware => return Ok(data.clone())
+    };
+
+    Ok(data
+        .clone()
+        .with_records(imputed)
+        .with_feature_names(data.feature_names())
+    )
+}
+
+fn impute_columns<F: Float, S: Fn(&mut Vec<F>) -> F>(records: &Array2<F>, statistic: S) -> Result<Array2<F>, ClusteringError> {
+    let mut imputed = records.to_owned();
+
+    for (feature, mut column) in imputed.axis_iter_mut(Axis(1)).enumerate() {
+        let mut present = column.iter().cloned().filter(|x| !x.is_nan()).collect::<Vec<_>>();
+
+        if present.len() == column.len() {
+            continue;
+        }
+
+        if present.is_empty() {
+            return Err(ClusteringError::InvalidParameters(format!("feature {} has no observed values to impute from", feature)));
+        }
+
+        let value = statistic(&mut present);
+        column.mapv_inplace(|x| if x.is_nan() { value } else { x });
+    }
+
+    Ok(imputed)
+}
+
+fn impute_knn<F: Float>(records: &Array2<F>, n_neighbors: usize) -> Result<Array2<F>, ClusteringError> {
+    if n_neighbors == 0 {
+        return Err(ClusteringError::InvalidParameters("kNN imputation needs at least one neighbour".into()));
+    }
+
+    let mut imputed = records.to_owned();
+
+    for i in 0..records.nrows() {
+        let row = records.row(i);
+
+        if row.iter().all(|x| !x.is_nan()) {
+            continue;
+        }
+
+        let mut neighbors = (0..records.nrows())
+            .filter(|&j| j != i)
+            .map(|j| (j, NanEuclidean.distance(row, records.row(j))))
+            .filter(|(_, distance)| distance.is_finite())
+            .collect::<Vec<_>>();
+
+        neighbors.sort_by(|(_, a), (_, b)| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+
+        for feature in (0..records.ncols()).filter(|&feature| row[feature].is_nan()) {
+            let donors = neighbors
+                .iter()
+                .map(|&(j, _)| records[[j, feature]])
+                .filter(|x| !x.is_nan())
+                .take(n_neighbors)
+                .collect::<Vec<_>>();
+
+            if donors.is_empty() {
+                return Err(ClusteringError::InvalidParameters(format!("no neighbour of row {} observes feature {}", i, feature)));
+            }
+
+            imputed[[i, feature]] = donors.iter().fold(F::zero(), |acc, &x| acc + x) / F::cast(donors.len());
+        }
+    }
+
+    Ok(imputed)
+}
+
+#[cfg(test)]
+mod tests {
+    use linfa::DatasetBase;
+    use ndarray::{array, Array1};
+
+    use super::*;
+
+    fn incomplete() -> Data {
+        let records = array![[1.0, 2.0], [f64::NAN, 4.0], [3.0, f64::NAN], [5.0, 8.0]];
+        DatasetBase::new(records, array![0, 1, 2, 3])
+    }
+
+    fn imputed(policy: Policy) -> Array2<f64> {
+        apply(&incomplete(), policy).unwrap().records
+    }
+
+    #[test]
+    fn mean_and_median_imputation() {
+        assert_eq!(imputed(Policy::Mean), array![[1.0, 2.0], [3.0, 4.0], [3.0, 14.0 / 3.0], [5.0, 8.0]]);
+        assert_eq!(imputed(Policy::Median), array![[1.0, 2.0], [3.0, 4.0], [3.0, 4.0], [5.0, 8.0]]);
+    }
+
+    #[test]
+    fn knn_imputation_averages_the_nearest_donors() {
+        // Rows 1 and 2 share no observed feature, so each is imputed from rows 0 and 3
+        assert_eq!(imputed(Policy::Knn { n_neighbors: 2 }), array![[1.0, 2.0], [3.0, 4.0], [3.0, 5.0], [5.0, 8.0]]);
+        assert!(apply(&incomplete(), Policy::Knn { n_neighbors: 0 }).is_err());
+    }
+
+    #[test]
+    fn drop_rows_keeps_complete_rows_and_their_targets() {
+        let data = apply(&incomplete(), Policy::DropRows).unwrap();
+
+        assert_eq!(data.records, array![[1.0, 2.0], [5.0, 8.0]]);
+        assert_eq!(data.targets, Array1::from(vec![0, 3]));
+        assert_eq!(count(&incomplete()), 2);
+    }
+}
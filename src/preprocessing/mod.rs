@@ -0,0 +1,339 @@
+pub mod reduction;
+pub mod missing;
+
+
+use std::cmp::Ordering;
+
+use linfa::Float;
+use ndarray::{s, Array1, Array2, Axis};
+
+use crate::{error::ClusteringError, utility::order::quantile, Data};
+
+#[derive(Debug, Clone)]
+pub enum Step {
+    // Zero mean and unit variance per feature
+    Standard,
+    // Every feature mapped onto [0, 1]
+    MinMax,
+    // Zero median and unit interquartile range per feature
+    Robust,
+    // ln(1 + x + shift) on the given features, the shift making the smallest value zero
+    Log { features: Vec<usize> },
+    // Replaces each of the given features by one indicator column per distinct value
+    OneHot { features: Vec<usize> }
+}
+
+#[derive(Debug, Clone)]
+enum Fitted<F> {
+    Affine { offset: Array1<F>, scale: Array1<F> },
+    Log { features: Vec<usize>, shifts: Vec<F> },
+    OneHot { n_features: usize, categories: Vec<Option<Vec<F>>> }
+}
+
+// Preprocessing steps fitted on a dataset, applied in order by `transform` and
+// undone in reverse order by `inverse`, e.g. to report centroids in original units
+#[derive(Debug, Clone)]
+pub struct Pipeline<F = f64> {
+    steps: Vec<Fitted<F>>,
+    input_names: Vec<String>,
+    feature_names: Vec<String>
+}
+
+// Features with no spread are only shifted
+fn nonzero<F: Float>(scale: F) -> F {
+    match scale > F::epsilon() {
+        true => scale,
+        false => F::one()
+    }
+}
+
+fn sorted<F: Float>(mut values: Vec<F>) -> Vec<F> {
+    values.sort_by(|a, b| a.partial_cmp(b).unwrap_or(Ordering::Equal));
+    values
+}
+
+fn check_features(features: &[usize], n_features: usize) -> Result<(), ClusteringError> {
+    match features.iter().find(|&&feature| feature >= n_features) {
+        Some(feature) => Err(ClusteringError::InvalidParameters(format!("feature {} out of range for {} features", feature, n_features))),
+        None => Ok(())
+    }
+}
+
+impl<F: Float> Fitted<F> {
+    fn fit(records: &Array2<F>, step: &Step) -> Result<Self, ClusteringError> {
+        let n_features = records.ncols();
+
+        if records.nrows() == 0 {
+            return Err(ClusteringError::InvalidParameters("cannot fit preprocessing on an empty dataset".into()));
+        }
+
+        let fitted = match step {
+            Step::Standard => {
+                let offset = records
+                    .mean_axis(Axis(0))
+                    .ok_or_else(|| ClusteringError::InvalidParameters("cannot fit preprocessing on an empty dataset".into()))?;
+                let scale = records.std_axis(Axis(0), F::zero()).mapv(nonzero);
+                Fitted::Affine { offset, scale }
+            },
+            Step::MinMax => {
+                let lower = records.fold_axis(Axis(0), F::infinity(), |&a, &b| a.min(b));
+                let upper = records.fold_axis(Axis(0), F::neg_infinity(), |&a, &b| a.max(b));
+                let scale = (upper - &lower).mapv(nonzero);
+                Fitted::Affine { offset: lower, scale }
+            },
+            Step::Robust => {
+                let mut offset = Array1::<F>::zeros(n_features);
+                let mut scale = Array1::<F>::ones(n_features);
+
+                for (feature, column) in records.axis_iter(Axis(1)).enumerate() {
+                    let values = sorted(column.to_vec());
+
+                    offset[feature] = quantile(&values, 0.5);
+                    scale[feature] = nonzero(quantile(&values, 0.75) - quantile(&values, 0.25));
+                }
+
+                Fitted::Affine { offset, scale }
+            },
+            Step::Log { features } => {
+                check_features(features, n_features)?;
+
+                let shifts = features
+                    .iter()
+                    .map(|&feature| {
+                        let minimum = records.column(feature).fold(F::infinity(), |a, &b| a.min(b));
+                        (-minimum).max(F::zero())
+                    })
+                    .collect();
+
+                Fitted::Log { features: features.clone(), shifts }
+            },
+            Step::OneHot { features } => {
+                check_features(features, n_features)?;
+
+                let categories = (0..n_features)
+                    .map(|feature| match features.contains(&feature) {
+                        true => {
+                            let mut values = sorted(records.column(feature).to_vec());
+                            values.dedup();
+                            Some(values)
+                        },
+                        false => None
+                    })
+                    .collect();
+
+                Fitted::OneHot { n_features, categories }
+            }
+        };
+
+        Ok(fitted)
+    }
+
+    fn names(&self, names: Vec<String>) -> Vec<String> {
+        match self {
+            Fitted::OneHot { categories, .. } => names
+                .into_iter()
+                .zip(categories)
+                .flat_map(|(name, values)| match values {
+                    Some(values) => values.iter().map(|value| format!("{}={}", name, value)).collect(),
+                    None => vec![name]
+                })
+                .collect(),
+            _ => names
+        }
+    }
+
+    fn transform(&self, records: &Array2<F>) -> Result<Array2<F>, ClusteringError> {
+        let transformed = match self {
+            Fitted::Affine { offset, scale } => (records - offset) / scale,
+            Fitted::Log { features, shifts } => {
+                let mut records = records.to_owned();
+
+                for (&feature, &shift) in features.iter().zip(shifts) {
+                    let mut column = records.column_mut(feature);
+
+                    if column.iter().any(|&x| x + shift <= -F::one()) {
+                        return Err(ClusteringError::InvalidParameters(format!("feature {} has values outside the domain of the fitted log transform", feature)));
+                    }
+
+                    column.mapv_inplace(|x| (x + shift).ln_1p());
+                }
+
+                records
+            },
+            Fitted::OneHot { categories, .. } => {
+                let columns = records
+                    .axis_iter(Axis(1))
+                    .zip(categories)
+                    .flat_map(|(column, values)| match values {
+                        Some(values) => values
+                            .iter()
+                            .map(|value| column.mapv(|x| if x == *value { F::one() } else { F::zero() }))
+                            .collect::<Vec<_>>(),
+                        None => vec![column.to_owned()]
+                    })
+                    .map(|column| column.insert_axis(Axis(1)))
+                    .collect::<Vec<_>>();
+
+                let views = columns.iter().map(|column| column.view()).collect::<Vec<_>>();
+
+                ndarray::concatenate(Axis(1), &views)?
+            }
+        };
+
+        Ok(transformed)
+    }
+
+    fn inverse(&self, records: &Array2<F>) -> Array2<F> {
+        match self {
+            Fitted::Affine { offset, scale } => records * scale + offset,
+            Fitted::Log { features, shifts } => {
+                let mut records = records.to_owned();
+
+                for (&feature, &shift) in features.iter().zip(shifts) {
+                    records.column_mut(feature).mapv_inplace(|y| y.exp_m1() - shift);
+                }
+
+                records
+            },
+            // Indicator blocks map back to their most active category
+            Fitted::OneHot { n_features, categories } => {
+                let mut original = Array2::<F>::zeros((records.nrows(), *n_features));
+                let mut column = 0;
+
+                for (feature, values) in categories.iter().enumerate() {
+                    match values {
+                        Some(values) => {
+                            for (i, row) in records.axis_iter(Axis(0)).enumerate() {
+                                let block = row.slice(s![column..column + values.len()]);
+                                let best = (0..values.len())
+                                    .max_by(|&a, &b| block[a].partial_cmp(&block[b]).unwrap_or(Ordering::Equal))
+                                    .unwrap_or(0);
+
+                                original[[i, feature]] = values[best];
+                            }
+
+                            column += values.len();
+                        },
+                        None => {
+                            original.column_mut(feature).assign(&records.column(column));
+                            column += 1;
+                        }
+                    }
+                }
+
+                original
+            }
+        }
+    }
+}
+
+impl<F: Float> Pipeline<F> {
+    // Every step is fitted on the output of the previous ones
+    pub fn fit(data: &Data<F>, steps: &[Step]) -> Result<Self, ClusteringError> {
+        missing::check(data)?;
+
+        let mut records = data.records.to_owned();
+        let mut fitted = Vec::with_capacity(steps.len());
+        let mut feature_names = data.feature_names();
+
+        for step in steps {
+            let step = Fitted::fit(&records, step)?;
+            records = step.transform(&records)?;
+            feature_names = step.names(feature_names);
+            fitted.push(step);
+        }
+
+        Ok(Pipeline { steps: fitted, input_names: data.feature_names(), feature_names })
+    }
+
+    pub fn fit_transform(data: &Data<F>, steps: &[Step]) -> Result<(Self, Data<F>), ClusteringError> {
+        let pipeline = Self::fit(data, steps)?;
+        let transformed = pipeline.transform(data)?;
+        Ok((pipeline, transformed))
+    }
+
+    pub fn transform(&self, data: &Data<F>) -> Result<Data<F>, ClusteringError> {
+        let records = self.transform_records(&data.records)?;
+
+        Ok(data
+            .clone()
+            .with_records(records)
+            .with_feature_names(self.feature_names.clone())
+        )
+    }
+
+    pub fn transform_records(&self, records: &Array2<F>) -> Result<Array2<F>, ClusteringError> {
+        if records.ncols() != self.input_names.len() {
+            return Err(ClusteringError::ShapeMismatch(format!("expected {} features, got {}", self.input_names.len(), records.ncols())));
+        }
+
+        self.steps
+            .iter()
+            .try_fold(records.to_owned(), |records, step| step.transform(&records))
+    }
+
+    // Maps transformed records (or centroids) back to the original units
+    pub fn inverse(&self, records: &Array2<F>) -> Result<Array2<F>, ClusteringError> {
+        if records.ncols() != self.feature_names.len() {
+            return Err(ClusteringError::ShapeMismatch(format!("expected {} features, got {}", self.feature_names.len(), records.ncols())));
+        }
+
+        Ok(self.steps
+            .iter()
+            .rev()
+            .fold(records.to_owned(), |records, step| step.inverse(&records))
+        )
+    }
+
+    pub fn inverse_transform(&self, data: &Data<F>) -> Result<Data<F>, ClusteringError> {
+        let records = self.inverse(&data.records)?;
+
+        Ok(data
+            .clone()
+            .with_records(records)
+            .with_feature_names(self.input_names.clone())
+        )
+    }
+
+    pub fn feature_names(&self) -> &[String] {
+        &self.feature_names
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linfa::DatasetBase;
+    use ndarray::array;
+
+    use super::*;
+
+    fn assert_close(a: &Array2<f64>, b: &Array2<f64>) {
+        assert_eq!(a.dim(), b.dim());
+        assert!(a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-9));
+    }
+
+    #[test]
+    fn inverse_transform_round_trip() {
+        let data = linfa_datasets::iris();
+        let steps = [Step::Log { features: vec![0, 2] }, Step::Standard, Step::Robust, Step::MinMax];
+
+        let (pipeline, transformed) = Pipeline::fit_transform(&data, &steps).unwrap();
+        let restored = pipeline.inverse_transform(&transformed).unwrap();
+
+        assert_close(&restored.records, &data.records);
+        assert_eq!(restored.feature_names(), data.feature_names());
+    }
+
+    #[test]
+    fn one_hot_round_trip() {
+        let records = array![[1.0, 0.5], [2.0, -1.0], [1.0, 3.0], [3.0, 0.0]];
+        let data = DatasetBase::new(records, Array1::<usize>::zeros(4));
+
+        let (pipeline, transformed) = Pipeline::fit_transform(&data, &[Step::OneHot { features: vec![0] }, Step::Standard]).unwrap();
+        assert_eq!(transformed.records.ncols(), 4);
+        assert_eq!(pipeline.feature_names()[..3], ["feature-0=1", "feature-0=2", "feature-0=3"]);
+
+        let restored = pipeline.inverse_transform(&transformed).unwrap();
+        assert_close(&restored.records, &data.records);
+    }
+}
@@ -0,0 +1,160 @@
+use linfa::Float;
+use ndarray::{s, Array1, Array2, Axis};
+use ndarray_rand::{rand_distr::Normal, RandomExt};
+
+use super::missing;
+use crate::{error::ClusteringError, utility::{linalg::symmetric_eigen, sampling::seeded_rng}, Data};
+
+// Principal component analysis on the sample covariance of the records
+#[derive(Debug, Clone)]
+pub struct Pca<F = f64> {
+    pub mean: Array1<F>,
+    // One principal axis per row
+    pub components: Array2<F>,
+    pub explained_variance: Array1<F>,
+    pub explained_variance_ratio: Array1<F>
+}
+
+impl<F: Float> Pca<F> {
+    pub fn fit(data: &Data<F>, n_components: usize) -> Result<Self, ClusteringError> {
+        missing::check(data)?;
+
+        let records = &data.records;
+        let (n_samples, n_features) = records.dim();
+
+        if n_samples < 2 {
+            return Err(ClusteringError::InvalidParameters("PCA needs at least two samples".into()));
+        }
+
+        if n_components == 0 || n_components > n_features {
+            return Err(ClusteringError::InvalidParameters(format!("cannot keep {} components of {} features", n_components, n_features)));
+        }
+
+        let mean = records.mean_axis(Axis(0)).ok_or_else(|| ClusteringError::InvalidParameters("empty dataset".into()))?;
+        let centered = records - &mean;
+        let covariance = centered.t().dot(&centered) / F::cast(n_samples - 1);
+
+        let (values, vectors) = symmetric_eigen(&covariance).ok_or_else(|| ClusteringError::Numerical("eigendecomposition failed".into()))?;
+        let values = values.mapv(|value| value.max(F::zero()));
+        let total = values.sum();
+
+        let mut components = vectors.t().slice(s![..n_components, ..]).to_owned();
+
+        // Deterministic signs: the largest loading of every axis is positive
+        for mut component in components.axis_iter_mut(Axis(0)) {
+            let largest = component.iter().cloned().fold(F::zero(), |a, b| if b.abs() > a.abs() { b } else { a });
+            if largest < F::zero() {
+                component.mapv_inplace(|x| -x);
+            }
+        }
+
+        let explained_variance = values.slice(s![..n_components]).to_owned();
+        let explained_variance_ratio = match total > F::zero() {
+            true => &explained_variance / total,
+            false => Array1::zeros(n_components)
+        };
+
+        Ok(Pca { mean, components, explained_variance, explained_variance_ratio })
+    }
+
+    pub fn n_components(&self) -> usize {
+        self.components.nrows()
+    }
+
+    pub fn transform_records(&self, records: &Array2<F>) -> Result<Array2<F>, ClusteringError> {
+        if records.ncols() != self.mean.len() {
+            return Err(ClusteringError::ShapeMismatch(format!("expected {} features, got {}", self.mean.len(), records.ncols())));
+        }
+
+        Ok((records - &self.mean).dot(&self.components.t()))
+    }
+
+    // Reduced dataset with features named PC1, PC2, ...
+    pub fn transform(&self, data: &Data<F>) -> Result<Data<F>, ClusteringError> {
+        let records = self.transform_records(&data.records)?;
+        let names = (1..=self.n_components()).map(|i| format!("PC{}", i)).collect::<Vec<_>>();
+
+        Ok(data.clone().with_records(records).with_feature_names(names))
+    }
+
+    // Maps reduced points (e.g. centroids) back to the original feature space
+    pub fn inverse(&self, reduced: &Array2<F>) -> Array2<F> {
+        reduced.dot(&self.components) + &self.mean
+    }
+}
+
+// Gaussian random projection (Johnson-Lindenstrauss), cheaper than PCA on wide data
+#[derive(Debug, Clone)]
+pub struct RandomProjection {
+    pub matrix: Array2<f64>
+}
+
+impl RandomProjection {
+    pub fn new(n_features: usize, n_components: usize, seed: Option<u64>) -> Result<Self, ClusteringError> {
+        if n_components == 0 {
+            return Err(ClusteringError::InvalidParameters("random projection needs at least one component".into()));
+        }
+
+        let mut rng = seeded_rng(seed);
+        let deviation = 1.0 / (n_components as f64).sqrt();
+        let matrix = Array2::random_using((n_features, n_components), Normal::new(0.0, deviation)?, &mut rng);
+
+        Ok(RandomProjection { matrix })
+    }
+
+    pub fn transform(&self, data: &Data) -> Result<Data, ClusteringError> {
+        if data.records.ncols() != self.matrix.nrows() {
+            return Err(ClusteringError::ShapeMismatch(format!("expected {} features, got {}", self.matrix.nrows(), data.records.ncols())));
+        }
+
+        let records = data.records.dot(&self.matrix);
+        let names = (1..=self.matrix.ncols()).map(|i| format!("RP{}", i)).collect::<Vec<_>>();
+
+        Ok(data.clone().with_records(records).with_feature_names(names))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use linfa::DatasetBase;
+    use ndarray::array;
+
+    use super::*;
+
+    fn dataset(records: Array2<f64>) -> Data {
+        let n_samples = records.nrows();
+        DatasetBase::new(records, Array1::<usize>::zeros(n_samples))
+    }
+
+    #[test]
+    fn explained_variance_of_known_covariance() {
+        // Sample covariance diag(2/3, 8/3)
+        let data = dataset(array![[1.0, 0.0], [-1.0, 0.0], [0.0, 2.0], [0.0, -2.0]]);
+        let pca = Pca::fit(&data, 2).unwrap();
+
+        assert!((&pca.explained_variance - &array![8.0 / 3.0, 2.0 / 3.0]).iter().all(|x| x.abs() < 1e-12));
+        assert!((&pca.explained_variance_ratio - &array![0.8, 0.2]).iter().all(|x| x.abs() < 1e-12));
+        assert!((&pca.components - &array![[0.0, 1.0], [1.0, 0.0]]).iter().all(|x| x.abs() < 1e-12));
+    }
+
+    #[test]
+    fn reconstructs_records_on_the_kept_components() {
+        // Collinear records are recovered exactly from a single component
+        let data = dataset(array![[0.0, 0.0], [1.0, 2.0], [2.0, 4.0], [3.0, 6.0]]);
+        let pca = Pca::fit(&data, 1).unwrap();
+
+        assert!((pca.explained_variance_ratio[0] - 1.0).abs() < 1e-12);
+
+        let reduced = pca.transform_records(&data.records).unwrap();
+        assert!((pca.inverse(&reduced) - &data.records).iter().all(|x| x.abs() < 1e-12));
+
+        // Keeping every component is a rotation, so iris is recovered as well
+        let iris = linfa_datasets::iris();
+        let pca = Pca::fit(&iris, 4).unwrap();
+
+        assert!((pca.explained_variance_ratio.sum() - 1.0).abs() < 1e-12);
+
+        let reduced = pca.transform_records(&iris.records).unwrap();
+        assert!((pca.inverse(&reduced) - &iris.records).iter().all(|x| x.abs() < 1e-9));
+    }
+}
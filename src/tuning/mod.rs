@@ -0,0 +1,297 @@
+use rand::Rng;
+
+use crate::model::{gravity, whales};
+use crate::error::ClusteringError;
+
+pub mod search;
+pub mod race;
+
+#[derive(Debug, Clone)]
+pub enum Domain {
+    Real { low: f64, high: f64, log_scale: bool },
+    Integer { low: usize, high: usize },
+    Categorical(Vec<&'static str>)
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Value {
+    Real(f64),
+    Integer(usize),
+    Category(&'static str)
+}
+
+impl Domain {
+    pub fn real(low: f64, high: f64, log_scale: bool) -> Result<Self, ClusteringError> {
+        let domain = Domain::Real { low, high, log_scale };
+        domain.validate()?;
+        Ok(domain)
+    }
+
+    pub fn integer(low: usize, high: usize) -> Result<Self, ClusteringError> {
+        let domain = Domain::Integer { low, high };
+        domain.validate()?;
+        Ok(domain)
+    }
+
+    pub fn categorical(options: Vec<&'static str>) -> Result<Self, ClusteringError> {
+        let domain = Domain::Categorical(options);
+        domain.validate()?;
+        Ok(domain)
+    }
+
+    // Bounds must be ordered, and positive on a log scale, so that sampling
+    // and the grid never panic or produce NaN
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        match self {
+            Domain::Real { low, high, .. } if !low.is_finite() || !high.is_finite() || low > high => Err(
+                ClusteringError::InvalidParameters(format!("real domain needs finite bounds with low <= high, got [{}, {}]", low, high))
+            ),
+            Domain::Real { low, log_scale: true, .. } if *low <= 0.0 => Err(
+                ClusteringError::InvalidParameters(format!("log-scale domain needs a positive lower bound, got {}", low))
+            ),
+            Domain::Integer { low, high } if low > high => Err(
+                ClusteringError::InvalidParameters(format!("integer domain needs low <= high, got [{}, {}]", low, high))
+            ),
+            Domain::Categorical(options) if options.is_empty() => Err(
+                ClusteringError::InvalidParameters("categorical domain needs at least one option".into())
+            ),
+            _ => Ok(())
+        }
+    }
+
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Value {
+        match self {
+            Domain::Real { low, high, log_scale: false } => Value::Real(rng.gen_range(*low..=*high)),
+            Domain::Real { low, high, log_scale: true } => {
+                let exponent = rng.gen_range(low.ln()..=high.ln());
+                Value::Real(exponent.exp())
+            },
+            Domain::Integer { low, high } => Value::Integer(rng.gen_range(*low..=*high)),
+            Domain::Categorical(options) => Value::Category(options[rng.gen_range(0..options.len())])
+        }
+    }
+
+    pub fn grid(&self, resolution: usize) -> Vec<Value> {
+        let resolution = resolution.max(1);
+
+        match self {
+            Domain::Real { low, high, log_scale: false } if resolution == 1 => vec![Value::Real((low + high) / 2.0)],
+            Domain::Real { low, high, log_scale: true } if resolution == 1 => vec![Value::Real((low * high).sqrt())],
+            Domain::Real { low, high, log_scale } => (0..resolution)
+                .map(|i| {
+                    let t = i as f64 / (resolution - 1) as f64;
+                    match log_scale {
+                        false => Value::Real(low + t * (high - low)),
+                        true => Value::Real((low.ln() + t * (high.ln() - low.ln())).exp())
+                    }
+                })
+                .collect(),
+            Domain::Integer { low, high } => {
+                let span = high - low;
+                let mut values = (0..resolution.min(span + 1))
+                    .map(|i| match resolution.min(span + 1) {
+                        1 => low + span / 2,
+                        n => low + i * span / (n - 1)
+                    })
+                    .collect::<Vec<_>>();
+                values.dedup();
+                values.into_iter().map(Value::Integer).collect()
+            },
+            Domain::Categorical(options) => options.iter().map(|&o| Value::Category(o)).collect()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct Space {
+    pub dimensions: Vec<(&'static str, Domain)>
+}
+
+impl Space {
+    pub fn new() -> Self {
+        Space { dimensions: Vec::new() }
+    }
+
+    pub fn with(mut self, name: &'static str, domain: Domain) -> Self {
+        self.dimensions.push((name, domain));
+        self
+    }
+
+    pub fn validate(&self) -> Result<(), ClusteringError> {
+        self.dimensions
+            .iter()
+            .try_for_each(|(_, domain)| domain.validate())
+    }
+
+    pub fn sample<R: Rng>(&self, rng: &mut R) -> Configuration {
+        let values = self.dimensions
+            .iter()
+            .map(|(name, domain)| (*name, domain.sample(rng)))
+            .collect();
+
+        Configuration { values }
+    }
+
+    pub fn grid(&self, resolution: usize) -> Vec<Configuration> {
+        self.dimensions
+            .iter()
+            .fold(vec![Configuration::default()], |configurations, (name, domain)| {
+                let values = domain.grid(resolution);
+                configurations
+                    .iter()
+                    .flat_map(|configuration| values.iter().map(move |&value| {
+                        let mut configuration = configuration.clone();
+                        configuration.values.push((*name, value));
+                        configuration
+                    }))
+                    .collect()
+            })
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Configuration {
+    pub values: Vec<(&'static str, Value)>
+}
+
+impl Configuration {
+    pub fn get(&self, name: &str) -> Option<Value> {
+        self.values
+            .iter()
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| *value)
+    }
+
+    pub fn real(&self, name: &str) -> Option<f64> {
+        match self.get(name)? {
+            Value::Real(x) => Some(x),
+            Value::Integer(x) => Some(x as f64),
+            Value::Category(_) => None
+        }
+    }
+
+    pub fn integer(&self, name: &str) -> Option<usize> {
+        match self.get(name)? {
+            Value::Integer(x) => Some(x),
+            _ => None
+        }
+    }
+
+    pub fn category(&self, name: &str) -> Option<&'static str> {
+        match self.get(name)? {
+            Value::Category(x) => Some(x),
+            _ => None
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Trial {
+    pub configuration: Configuration,
+    pub scores: Vec<f64>,
+    pub survived: bool
+}
+
+impl Trial {
+    pub fn mean(&self) -> f64 {
+        self.scores.iter().sum::<f64>() / self.scores.len() as f64
+    }
+
+    pub fn std(&self) -> f64 {
+        let mean = self.mean();
+        let n = self.scores.len() as f64;
+        (self.scores.iter().map(|s| (s - mean).powi(2)).sum::<f64>() / n).sqrt()
+    }
+}
+
+// Scores are maximized: pass e.g. accuracy or fitness, not a loss.
+#[derive(Debug, Clone)]
+pub struct Results {
+    pub trials: Vec<Trial>
+}
+
+impl Results {
+    pub fn best(&self) -> Option<&Trial> {
+        self.trials
+            .iter()
+            .filter(|trial| trial.survived && !trial.scores.is_empty())
+            .max_by(|a, b| a.mean().total_cmp(&b.mean()))
+    }
+
+    pub fn table(&self) -> Vec<(Configuration, f64, f64, usize)> {
+        self.trials
+            .iter()
+            .map(|trial| (trial.configuration.clone(), trial.mean(), trial.std(), trial.scores.len()))
+            .collect()
+    }
+}
+
+pub(crate) fn evaluate<F>(configuration: &Configuration, seeds: &[u64], objective: &F) -> Result<Vec<f64>, ClusteringError>
+where
+    F: Fn(&Configuration, u64) -> Result<f64, ClusteringError>
+{
+    seeds
+        .iter()
+        .map(|&seed| objective(configuration, seed))
+        .collect()
+}
+
+pub fn gravity_space() -> Result<Space, ClusteringError> {
+    let space = Space::new()
+        .with("n_agents", Domain::integer(5, 50)?)
+        .with("initial_gravity", Domain::real(0.01, 10.0, true)?)
+        .with("gravity_decay", Domain::real(0.001, 1.0, true)?)
+        .with("distance", Domain::categorical(vec!["Cosine", "L1", "L2", "LInf", "Hamming"])?)
+        .with("normalization", Domain::categorical(vec!["Logistic", "MinMax", "ReLU"])?);
+
+    Ok(space)
+}
+
+pub fn whales_space() -> Result<Space, ClusteringError> {
+    let space = Space::new()
+        .with("n_agents", Domain::integer(5, 100)?)
+        .with("spiral_constant", Domain::real(0.1, 5.0, false)?)
+        .with("n_spiral_samples", Domain::integer(1, 100)?);
+
+    Ok(space)
+}
+
+pub fn gravity_parameters(base: gravity::Parameters, configuration: &Configuration, seed: u64) -> gravity::Parameters {
+    let distance = match configuration.category("distance") {
+        Some("Cosine") => gravity::Distance::Cosine,
+        Some("L1") => gravity::Distance::L1,
+        Some("L2") => gravity::Distance::L2,
+        Some("LInf") => gravity::Distance::LInf,
+        Some("Hamming") => gravity::Distance::Hamming,
+        _ => base.distance
+    };
+
+    let normalization = match configuration.category("normalization") {
+        Some("Logistic") => gravity::Normalization::Logistic,
+        Some("MinMax") => gravity::Normalization::MinMax,
+        Some("ReLU") => gravity::Normalization::ReLU,
+        _ => base.normalization
+    };
+
+    gravity::Parameters {
+        n_agents: configuration.integer("n_agents").unwrap_or(base.n_agents),
+        max_iterations: configuration.integer("max_iterations").unwrap_or(base.max_iterations),
+        initial_gravity: configuration.real("initial_gravity").unwrap_or(base.initial_gravity),
+        gravity_decay: configuration.real("gravity_decay").unwrap_or(base.gravity_decay),
+        distance,
+        normalization,
+        seed: Some(seed),
+        ..base
+    }
+}
+
+pub fn whales_parameters(base: whales::Parameters, configuration: &Configuration, seed: u64) -> whales::Parameters {
+    whales::Parameters {
+        n_agents: configuration.integer("n_agents").unwrap_or(base.n_agents),
+        max_iterations: configuration.integer("max_iterations").unwrap_or(base.max_iterations),
+        spiral_constant: configuration.real("spiral_constant").unwrap_or(base.spiral_constant),
+        n_spiral_samples: configuration.integer("n_spiral_samples").unwrap_or(base.n_spiral_samples),
+        seed: Some(seed),
+        ..base
+    }
+}
@@ -0,0 +1,260 @@
+use super::{Configuration, Results, Trial};
+use crate::error::ClusteringError;
+
+// F-race (Birattari et al., 2002): candidates are evaluated seed by seed and
+// discarded as soon as the Friedman test finds them significantly worse.
+#[derive(Debug, Clone, Copy)]
+pub struct Parameters {
+    pub min_blocks: usize,
+    pub min_survivors: usize,
+    pub confidence: f64
+}
+
+impl Default for Parameters {
+    fn default() -> Self {
+        Parameters {
+            min_blocks: 5,
+            min_survivors: 1,
+            confidence: 0.95
+        }
+    }
+}
+
+pub fn race<F>(
+    candidates: Vec<Configuration>,
+    seeds: &[u64],
+    params: Parameters,
+    objective: F
+) -> Result<Results, ClusteringError>
+where
+    F: Fn(&Configuration, u64) -> Result<f64, ClusteringError>
+{
+    let Parameters { min_blocks, min_survivors, confidence } = params;
+
+    if !(confidence > 0.0 && confidence < 1.0) {
+        return Err(ClusteringError::InvalidParameters(format!("confidence must lie in (0, 1), got {}", confidence)));
+    }
+
+    let mut trials = candidates
+        .into_iter()
+        .map(|configuration| Trial { configuration, scores: Vec::new(), survived: true })
+        .collect::<Vec<_>>();
+
+    for (block, &seed) in seeds.iter().enumerate() {
+        for trial in trials.iter_mut().filter(|trial| trial.survived) {
+            let score = objective(&trial.configuration, seed)?;
+            trial.scores.push(score);
+        }
+
+        let alive = (0..trials.len())
+            .filter(|&i| trials[i].survived)
+            .collect::<Vec<_>>();
+
+        if block + 1 < min_blocks || alive.len() <= min_survivors.max(1) {
+            continue;
+        }
+
+        let eliminated = friedman_eliminate(&trials, &alive, block + 1, confidence);
+
+        for i in eliminated {
+            trials[i].survived = false;
+        }
+    }
+
+    Ok(Results { trials })
+}
+
+fn friedman_eliminate(trials: &[Trial], alive: &[usize], n_blocks: usize, confidence: f64) -> Vec<usize> {
+    let blocks = (0..n_blocks)
+        .map(|block| alive.iter().map(|&i| trials[i].scores[block]).collect::<Vec<_>>())
+        .collect::<Vec<_>>();
+
+    let test = Friedman::new(&blocks);
+    let k = alive.len() as f64;
+
+    match test.statistic() {
+        Some(statistic) if statistic > chi_squared_quantile(confidence, k - 1.0) => (),
+        _ => return Vec::new()
+    }
+
+    // Conover's post-hoc comparison against the best (lowest rank sum) candidate
+    let best = test.rank_sums.iter().cloned().fold(f64::INFINITY, f64::min);
+    let threshold = test.conover_threshold(confidence);
+
+    alive
+        .iter()
+        .zip(test.rank_sums)
+        .filter(|(_, r)| r - best > threshold)
+        .map(|(&i, _)| i)
+        .collect()
+}
+
+// Rank sums of the candidates over the blocks, and the sum of all squared ranks
+struct Friedman {
+    rank_sums: Vec<f64>,
+    rank_squares: f64,
+    n_blocks: f64
+}
+
+impl Friedman {
+    fn new(blocks: &[Vec<f64>]) -> Self {
+        let mut rank_sums = vec![0.0; blocks.first().map_or(0, Vec::len)];
+        let mut rank_squares = 0.0;
+
+        for scores in blocks {
+            for (j, rank) in ranks(scores).into_iter().enumerate() {
+                rank_sums[j] += rank;
+                rank_squares += rank * rank;
+            }
+        }
+
+        Friedman { rank_sums, rank_squares, n_blocks: blocks.len() as f64 }
+    }
+
+    fn n_candidates(&self) -> f64 {
+        self.rank_sums.len() as f64
+    }
+
+    // Tie-corrected statistic (Conover, 1999), None when every block is a full tie
+    fn statistic(&self) -> Option<f64> {
+        let k = self.n_candidates();
+        let b = self.n_blocks;
+
+        let correction = b * k * (k + 1.0).powi(2) / 4.0;
+        let denominator = self.rank_squares - correction;
+
+        if denominator <= 0.0 {
+            return None;
+        }
+
+        let spread = self.rank_sums
+            .iter()
+            .map(|r| (r - b * (k + 1.0) / 2.0).powi(2))
+            .sum::<f64>();
+
+        Some((k - 1.0) * spread / denominator)
+    }
+
+    // Least significant difference between two rank sums
+    fn conover_threshold(&self, confidence: f64) -> f64 {
+        let k = self.n_candidates();
+        let b = self.n_blocks;
+
+        let df = (b - 1.0) * (k - 1.0);
+        let squares = self.rank_sums.iter().map(|r| r * r).sum::<f64>() / b;
+        let spread = (2.0 * b * (self.rank_squares - squares) / df).sqrt();
+
+        student_quantile(1.0 - (1.0 - confidence) / 2.0, df) * spread
+    }
+}
+
+// Rank 1 is the highest score, ties share their average rank
+fn ranks(scores: &[f64]) -> Vec<f64> {
+    let mut order = (0..scores.len()).collect::<Vec<_>>();
+    order.sort_by(|&a, &b| scores[b].total_cmp(&scores[a]));
+
+    let mut ranks = vec![0.0; scores.len()];
+    let mut start = 0;
+
+    while start < order.len() {
+        let mut end = start;
+        while end + 1 < order.len() && scores[order[end + 1]] == scores[order[start]] {
+            end += 1;
+        }
+
+        let rank = (start + end) as f64 / 2.0 + 1.0;
+        order[start..=end].iter().for_each(|&i| ranks[i] = rank);
+
+        start = end + 1;
+    }
+
+    ranks
+}
+
+// Abramowitz & Stegun 26.2.23
+fn normal_quantile(p: f64) -> f64 {
+    let (q, sign) = match p < 0.5 {
+        true => (p, -1.0),
+        false => (1.0 - p, 1.0)
+    };
+
+    let t = (-2.0 * q.ln()).sqrt();
+    let numerator = 2.515517 + 0.802853 * t + 0.010328 * t * t;
+    let denominator = 1.0 + 1.432788 * t + 0.189269 * t * t + 0.001308 * t * t * t;
+
+    sign * (t - numerator / denominator)
+}
+
+// Wilson-Hilferty approximation
+fn chi_squared_quantile(p: f64, df: f64) -> f64 {
+    let z = normal_quantile(p);
+    let h = 2.0 / (9.0 * df);
+
+    df * (1.0 - h + z * h.sqrt()).powi(3)
+}
+
+// First-order Cornish-Fisher expansion around the normal quantile
+fn student_quantile(p: f64, df: f64) -> f64 {
+    let z = normal_quantile(p);
+
+    z + (z.powi(3) + z) / (4.0 * df)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Hollander & Wolfe (1973), p. 140: rounding-first-base times of 22
+    // players under three methods, as in R's friedman.test example
+    const ROUNDING_TIMES: [[f64; 3]; 22] = [
+        [5.40, 5.50, 5.55], [5.85, 5.70, 5.75], [5.20, 5.60, 5.50], [5.55, 5.50, 5.40],
+        [5.90, 5.85, 5.70], [5.45, 5.55, 5.60], [5.40, 5.40, 5.35], [5.45, 5.50, 5.35],
+        [5.25, 5.15, 5.00], [5.85, 5.80, 5.70], [5.25, 5.20, 5.10], [5.65, 5.55, 5.45],
+        [5.60, 5.35, 5.45], [5.05, 5.00, 4.95], [5.50, 5.50, 5.40], [5.45, 5.55, 5.50],
+        [5.55, 5.55, 5.35], [5.45, 5.50, 5.55], [5.50, 5.45, 5.25], [5.65, 5.60, 5.40],
+        [5.70, 5.65, 5.55], [6.30, 6.30, 6.25]
+    ];
+
+    fn rounding_times() -> Friedman {
+        Friedman::new(&ROUNDING_TIMES.iter().map(|block| block.to_vec()).collect::<Vec<_>>())
+    }
+
+    #[test]
+    fn friedman_statistic_matches_published_value() {
+        let test = rounding_times();
+
+        // Ranks run from the highest score, so the sums mirror the published 53, 47, 32
+        assert_eq!(test.rank_sums, vec![35.0, 41.0, 56.0]);
+        assert!((test.statistic().unwrap() - 11.143).abs() < 1e-3);
+    }
+
+    #[test]
+    fn conover_threshold_matches_tabulated_quantile() {
+        // t(0.975, 42) = 2.018, and the pooled spread is sqrt(44 * (306 - 6042 / 22) / 42)
+        let expected = 2.018 * (44.0 * (306.0 - 6042.0 / 22.0) / 42.0_f64).sqrt();
+
+        assert!((rounding_times().conover_threshold(0.95) - expected).abs() < 0.05);
+    }
+
+    #[test]
+    fn quantiles_match_published_tables() {
+        assert!((normal_quantile(0.975) - 1.95996).abs() < 5e-4);
+        assert!((normal_quantile(0.05) + 1.64485).abs() < 5e-4);
+
+        for (df, expected) in [(2.0, 5.991), (5.0, 11.070), (10.0, 18.307)] {
+            assert!((chi_squared_quantile(0.95, df) / expected - 1.0).abs() < 0.01);
+        }
+
+        for (df, expected) in [(10.0, 2.228), (30.0, 2.042), (120.0, 1.980)] {
+            assert!((student_quantile(0.975, df) / expected - 1.0).abs() < 0.02);
+        }
+    }
+
+    #[test]
+    fn rejects_confidence_outside_unit_interval() {
+        for confidence in [0.0, 1.0, f64::NAN] {
+            let params = Parameters { confidence, ..Parameters::default() };
+            assert!(race(Vec::new(), &[0], params, |_, _| Ok(0.0)).is_err());
+        }
+    }
+}
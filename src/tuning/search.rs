@@ -0,0 +1,53 @@
+use super::{evaluate, Configuration, Results, Space, Trial};
+use crate::utility::sampling::seeded_rng;
+use crate::error::ClusteringError;
+
+pub fn grid_search<F>(
+    space: &Space,
+    resolution: usize,
+    seeds: &[u64],
+    objective: F
+) -> Result<Results, ClusteringError>
+where
+    F: Fn(&Configuration, u64) -> Result<f64, ClusteringError>
+{
+    space.validate()?;
+
+    run(space.grid(resolution), seeds, &objective)
+}
+
+pub fn random_search<F>(
+    space: &Space,
+    n_configurations: usize,
+    seeds: &[u64],
+    sampling_seed: Option<u64>,
+    objective: F
+) -> Result<Results, ClusteringError>
+where
+    F: Fn(&Configuration, u64) -> Result<f64, ClusteringError>
+{
+    space.validate()?;
+
+    let mut rng = seeded_rng(sampling_seed);
+
+    let configurations = (0..n_configurations)
+        .map(|_| space.sample(&mut rng))
+        .collect();
+
+    run(configurations, seeds, &objective)
+}
+
+fn run<F>(configurations: Vec<Configuration>, seeds: &[u64], objective: &F) -> Result<Results, ClusteringError>
+where
+    F: Fn(&Configuration, u64) -> Result<f64, ClusteringError>
+{
+    let trials = configurations
+        .into_iter()
+        .map(|configuration| {
+            let scores = evaluate(&configuration, seeds, objective)?;
+            Ok(Trial { configuration, scores, survived: true })
+        })
+        .collect::<Result<Vec<_>, ClusteringError>>()?;
+
+    Ok(Results { trials })
+}
@@ -1,12 +1,13 @@
 
+use linfa::Float;
 use ndarray::{Array, Dimension};
 
 pub trait Norm<T> {
     fn l2(&self) -> T;
 }
 
-impl<D: Dimension> Norm<f64> for Array<f64, D> {
-    fn l2(&self) -> f64 {
-        (self * self).sum()
+impl<F: Float, D: Dimension> Norm<F> for Array<F, D> {
+    fn l2(&self) -> F {
+        (self * self).sum().sqrt()
     }
 }
\ No newline at end of file
@@ -0,0 +1,124 @@
+use std::cmp::Ordering;
+
+use linfa::Float;
+use ndarray::{Array1, Array2, Axis};
+
+// Gauss-Jordan elimination with partial pivoting, returns the inverse and the determinant
+pub fn invert<F: Float>(matrix: &Array2<F>) -> Option<(Array2<F>, F)> {
+    let n = matrix.nrows();
+
+    if n != matrix.ncols() {
+        return None;
+    }
+
+    let mut a = matrix.to_owned();
+    let mut inverse = Array2::<F>::eye(n);
+    let mut determinant = F::one();
+    let smallest = F::cast(1e-300).max(F::min_positive_value());
+
+    for col in 0..n {
+        let pivot = (col..n).max_by(|&i, &j| a[[i, col]].abs().partial_cmp(&a[[j, col]].abs()).unwrap_or(Ordering::Equal))?;
+
+        if a[[pivot, col]].abs() < smallest {
+            return None;
+        }
+
+        if pivot != col {
+            for k in 0..n {
+                a.swap([pivot, k], [col, k]);
+                inverse.swap([pivot, k], [col, k]);
+            }
+            determinant = -determinant;
+        }
+
+        let value = a[[col, col]];
+        determinant *= value;
+
+        for k in 0..n {
+            a[[col, k]] /= value;
+            inverse[[col, k]] /= value;
+        }
+
+        for row in (0..n).filter(|&row| row != col) {
+            let factor = a[[row, col]];
+            if factor == F::zero() {
+                continue;
+            }
+            for k in 0..n {
+                let (pivot_a, pivot_inverse) = (a[[col, k]], inverse[[col, k]]);
+                a[[row, k]] -= factor * pivot_a;
+                inverse[[row, k]] -= factor * pivot_inverse;
+            }
+        }
+    }
+
+    Some((inverse, determinant))
+}
+
+// Cyclic Jacobi eigenvalue algorithm for symmetric matrices, returns the
+// eigenvalues in descending order with the eigenvectors as matching columns
+pub fn symmetric_eigen<F: Float>(matrix: &Array2<F>) -> Option<(Array1<F>, Array2<F>)> {
+    let n = matrix.nrows();
+
+    if n != matrix.ncols() {
+        return None;
+    }
+
+    let mut a = matrix.to_owned();
+    let mut vectors = Array2::<F>::eye(n);
+
+    // Off-diagonal mass below which the matrix counts as diagonal, relative
+    // to its norm when single precision cannot reach the absolute bound
+    let norm = a.iter().fold(F::zero(), |acc, &x| acc + x * x);
+    let tolerance = F::cast(1e-22).max(F::epsilon() * F::epsilon() * norm);
+    let smallest = F::cast(1e-300).max(F::min_positive_value());
+
+    for _ in 0..100 {
+        let off_diagonal = (0..n)
+            .flat_map(|i| (0..n).filter(move |&j| j != i).map(move |j| (i, j)))
+            .fold(F::zero(), |acc, (i, j)| acc + a[[i, j]] * a[[i, j]]);
+
+        if off_diagonal < tolerance {
+            break;
+        }
+
+        for p in 0..n {
+            for q in p + 1..n {
+                if a[[p, q]].abs() < smallest {
+                    continue;
+                }
+
+                let theta = (a[[q, q]] - a[[p, p]]) / (F::cast(2.0) * a[[p, q]]);
+                let t = theta.signum() / (theta.abs() + (theta * theta + F::one()).sqrt());
+                let c = F::one() / (t * t + F::one()).sqrt();
+                let s = t * c;
+
+                for k in 0..n {
+                    let (akp, akq) = (a[[k, p]], a[[k, q]]);
+                    a[[k, p]] = c * akp - s * akq;
+                    a[[k, q]] = s * akp + c * akq;
+                }
+
+                for k in 0..n {
+                    let (apk, aqk) = (a[[p, k]], a[[q, k]]);
+                    a[[p, k]] = c * apk - s * aqk;
+                    a[[q, k]] = s * apk + c * aqk;
+                }
+
+                for k in 0..n {
+                    let (vkp, vkq) = (vectors[[k, p]], vectors[[k, q]]);
+                    vectors[[k, p]] = c * vkp - s * vkq;
+                    vectors[[k, q]] = s * vkp + c * vkq;
+                }
+            }
+        }
+    }
+
+    let mut order = (0..n).collect::<Vec<_>>();
+    order.sort_by(|&i, &j| a[[j, j]].partial_cmp(&a[[i, i]]).unwrap_or(Ordering::Equal));
+
+    let values = order.iter().map(|&i| a[[i, i]]).collect::<Array1<F>>();
+    let vectors = vectors.select(Axis(1), &order);
+
+    Some((values, vectors))
+}
@@ -1,4 +1,5 @@
 pub mod normalization;
 pub mod sampling;
 pub mod order;
-pub mod array;
\ No newline at end of file
+pub mod array;
+pub mod linalg;
\ No newline at end of file
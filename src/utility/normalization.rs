@@ -1,4 +1,4 @@
-use std::cmp::Ordering;
+use linfa::Float;
 use ndarray::{Array2, Axis};
 
 pub trait Normalize {
@@ -7,13 +7,13 @@ pub trait Normalize {
     fn relu_inplace(&mut self) -> &mut Self;
 }
 
-impl Normalize for Array2<f64> {
+impl<F: Float> Normalize for Array2<F> {
     fn minmax_inplace(&mut self) -> &mut Self {
         self.map_axis_mut(
             Axis(0),
             |mut ax| {
-                let maximum = ax.iter().fold(f64::NEG_INFINITY, |a, &b| a.max(b));
-                let minimum = ax.iter().fold(f64::INFINITY, |a, &b| a.min(b));
+                let maximum = ax.iter().fold(F::neg_infinity(), |a, &b| a.max(b));
+                let minimum = ax.iter().fold(F::infinity(), |a, &b| a.min(b));
                 let range = maximum - minimum;
                 ax.mapv_inplace(|x| (x - minimum) / range);
             }
@@ -26,7 +26,7 @@ impl Normalize for Array2<f64> {
         self.map_axis_mut(
             Axis(0),
             |mut ax| {
-                ax.mapv_inplace(|x| 1.0 / (1.0 + (-x).exp()));
+                ax.mapv_inplace(|x| F::one() / (F::one() + (-x).exp()));
             }
         );
 
@@ -37,9 +37,9 @@ impl Normalize for Array2<f64> {
         self.map_axis_mut(
             Axis(0),
             |mut ax| {
-                ax.mapv_inplace(|x| match x.total_cmp(&0.0) {
-                    Ordering::Greater => x,
-                    _ => 0.0
+                ax.mapv_inplace(|x| match x > F::zero() {
+                    true => x,
+                    false => F::zero()
                 })
             }
         );
@@ -1,4 +1,5 @@
 use std::cmp::Ordering;
+use linfa::Float;
 use num_traits::Num;
 
 pub trait Ordered<T> {
@@ -91,4 +92,13 @@ impl <T: Num + Clone + Copy + std::cmp::PartialOrd + std::cmp::PartialEq> Ordere
 
         Some(index)
     }
-}
\ No newline at end of file
+}
+
+// Linearly interpolated quantile of already sorted, non-empty values
+pub fn quantile<F: Float>(sorted: &[F], q: f64) -> F {
+    let position = q * (sorted.len() - 1) as f64;
+    let (lower, upper) = (position.floor() as usize, position.ceil() as usize);
+    let fraction = F::cast(position - lower as f64);
+
+    sorted[lower] * (F::one() - fraction) + sorted[upper] * fraction
+}
@@ -1,7 +1,10 @@
-use std::ops::Range;
+use std::{f64::consts::PI, ops::Range};
 use num_traits::Num;
 
-use rand::{distributions::uniform::{SampleRange, SampleUniform}, rngs::ThreadRng, Rng};
+use ndarray_rand::rand_distr::StandardNormal;
+use rand::{distributions::{uniform::{SampleRange, SampleUniform}, Distribution}, rngs::StdRng, Rng, SeedableRng};
+
+use crate::error::ClusteringError;
 
 pub trait ExtendedRng<T, R> where T: SampleUniform, R: SampleRange<T> {
     fn gen_zero_to(&mut self, lim: T) -> T;
@@ -9,7 +12,7 @@ pub trait ExtendedRng<T, R> where T: SampleUniform, R: SampleRange<T> {
     fn gen_distinct_pair_range(&mut self, range: R) -> (T, T);
 }
 
-impl<N: Num + Clone + Copy + std::cmp::PartialOrd + std::cmp::PartialEq + SampleUniform> ExtendedRng<N, Range<N>> for ThreadRng {
+impl<G: Rng, N: Num + Clone + Copy + std::cmp::PartialOrd + std::cmp::PartialEq + SampleUniform> ExtendedRng<N, Range<N>> for G {
     fn gen_zero_to(&mut self, lim: N) -> N {
         self.gen_range(N::zero()..lim)
     }
@@ -35,3 +38,70 @@ impl<N: Num + Clone + Copy + std::cmp::PartialOrd + std::cmp::PartialEq + Sample
         (x, y)
     }
 }
+
+pub fn seeded_rng(seed: Option<u64>) -> StdRng {
+    match seed {
+        Some(seed) => StdRng::seed_from_u64(seed),
+        None => StdRng::from_entropy()
+    }
+}
+
+// Lanczos approximation (g = 7, n = 9) of the gamma function for positive arguments
+fn gamma(x: f64) -> f64 {
+    const COEFFICIENTS: [f64; 9] = [
+        0.999_999_999_999_809_9,
+        676.520_368_121_885_1,
+        -1_259.139_216_722_402_8,
+        771.323_428_777_653_1,
+        -176.615_029_162_140_6,
+        12.507_343_278_686_905,
+        -0.138_571_095_265_720_12,
+        9.984_369_578_019_572e-6,
+        1.505_632_735_149_311_6e-7,
+    ];
+
+    if x < 0.5 {
+        return PI / ((PI * x).sin() * gamma(1.0 - x));
+    }
+
+    let x = x - 1.0;
+    let t = x + 7.5;
+
+    let series = COEFFICIENTS[1..]
+        .iter()
+        .enumerate()
+        .fold(COEFFICIENTS[0], |acc, (i, c)| acc + c / (x + i as f64 + 1.0));
+
+    (2.0 * PI).sqrt() * t.powf(x + 0.5) * (-t).exp() * series
+}
+
+// Heavy-tailed Lévy-stable steps drawn with Mantegna's (1994) algorithm
+#[derive(Debug, Clone, Copy)]
+pub struct Levy {
+    exponent: f64,
+    sigma: f64
+}
+
+impl Levy {
+    // Mantegna's algorithm only holds for stability exponents in (0, 2]
+    pub fn new(exponent: f64) -> Result<Self, ClusteringError> {
+        if !(exponent > 0.0 && exponent <= 2.0) {
+            return Err(ClusteringError::InvalidParameters(format!("Lévy exponent must lie in (0, 2], got {}", exponent)));
+        }
+
+        let numerator = gamma(1.0 + exponent) * (PI * exponent / 2.0).sin();
+        let denominator = gamma((1.0 + exponent) / 2.0) * exponent * 2f64.powf((exponent - 1.0) / 2.0);
+        let sigma = (numerator / denominator).powf(1.0 / exponent);
+
+        Ok(Levy { exponent, sigma })
+    }
+}
+
+impl Distribution<f64> for Levy {
+    fn sample<G: Rng + ?Sized>(&self, rng: &mut G) -> f64 {
+        let u: f64 = rng.sample::<f64, _>(StandardNormal) * self.sigma;
+        let v: f64 = rng.sample(StandardNormal);
+
+        u / v.abs().powf(1.0 / self.exponent)
+    }
+}